@@ -0,0 +1,326 @@
+use crate::{
+    api::{Endpoint, ZebedeeApi},
+    charges::*,
+    email::*,
+    errors::{ApiError, ErrorMsg},
+    gamertag::*,
+    ids::*,
+    internal_transfer::*,
+    keysend::*,
+    ln_address::*,
+    login_with_zbd::*,
+    payments::*,
+    utilities::*,
+    wallet::*,
+    withdrawal_request::*,
+    StdResp,
+};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, MutexGuard},
+};
+
+/// A call received by a [`FakeZebedeeApi`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiCall {
+    pub endpoint: Endpoint,
+    /// Request body or path parameters of the call as JSON
+    pub args: Value,
+}
+
+#[derive(Debug, Clone)]
+enum FakeResponse {
+    Body(Value),
+    Error(String),
+}
+
+#[derive(Debug, Default)]
+struct FakeState {
+    once: HashMap<Endpoint, VecDeque<FakeResponse>>,
+    always: HashMap<Endpoint, FakeResponse>,
+    calls: Vec<ApiCall>,
+}
+
+/// In-memory [`ZebedeeApi`] for unit tests, nothing is sent over the network.
+///
+/// Responses are programmed per [`Endpoint`] as the JSON body the API would return.
+/// One-shot responses are used first, in order, then the response set with `respond` or `fail`.
+/// Calls to an endpoint without a response fail with [`ErrorMsg::NoFakeResponse`].
+#[derive(Debug, Default)]
+pub struct FakeZebedeeApi {
+    state: Mutex<FakeState>,
+}
+
+impl FakeZebedeeApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Answers every later call to `endpoint` with `body`
+    pub fn respond<T: Serialize>(&self, endpoint: Endpoint, body: &T) -> crate::Result<()> {
+        let body = serde_json::to_value(body)?;
+        self.state()
+            .always
+            .insert(endpoint, FakeResponse::Body(body));
+        Ok(())
+    }
+
+    /// Answers the next call to `endpoint` with `body`
+    pub fn respond_once<T: Serialize>(&self, endpoint: Endpoint, body: &T) -> crate::Result<()> {
+        let body = serde_json::to_value(body)?;
+        self.state()
+            .once
+            .entry(endpoint)
+            .or_default()
+            .push_back(FakeResponse::Body(body));
+        Ok(())
+    }
+
+    /// Fails every later call to `endpoint` with an API error carrying `message`
+    pub fn fail(&self, endpoint: Endpoint, message: &str) {
+        self.state()
+            .always
+            .insert(endpoint, FakeResponse::Error(message.to_owned()));
+    }
+
+    /// Fails the next call to `endpoint` with an API error carrying `message`
+    pub fn fail_once(&self, endpoint: Endpoint, message: &str) {
+        self.state()
+            .once
+            .entry(endpoint)
+            .or_default()
+            .push_back(FakeResponse::Error(message.to_owned()));
+    }
+
+    /// Every call received so far, oldest first
+    pub fn calls(&self) -> Vec<ApiCall> {
+        self.state().calls.clone()
+    }
+
+    pub fn calls_to(&self, endpoint: Endpoint) -> Vec<ApiCall> {
+        self.state()
+            .calls
+            .iter()
+            .filter(|call| call.endpoint == endpoint)
+            .cloned()
+            .collect()
+    }
+
+    pub fn call_count(&self, endpoint: Endpoint) -> usize {
+        self.state()
+            .calls
+            .iter()
+            .filter(|call| call.endpoint == endpoint)
+            .count()
+    }
+
+    pub fn clear_calls(&self) {
+        self.state().calls.clear();
+    }
+
+    fn call<T: DeserializeOwned>(&self, endpoint: Endpoint, args: Value) -> crate::Result<T> {
+        let response = {
+            let mut state = self.state();
+            state.calls.push(ApiCall { endpoint, args });
+            state
+                .once
+                .get_mut(&endpoint)
+                .and_then(VecDeque::pop_front)
+                .or_else(|| state.always.get(&endpoint).cloned())
+        };
+
+        match response {
+            Some(FakeResponse::Body(body)) => Ok(serde_json::from_value(body)?),
+            Some(FakeResponse::Error(message)) => Err(ApiError {
+                message: Some(message),
+                ..Default::default()
+            }
+            .into()),
+            None => Err(ErrorMsg::NoFakeResponse(endpoint).into()),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ZebedeeApi for FakeZebedeeApi {
+    async fn get_wallet_details(&self) -> crate::Result<WalletInfoResponse> {
+        self.call(Endpoint::GetWalletDetails, Value::Null)
+    }
+
+    async fn keysend(&self, keysend_payload: &Keysend) -> crate::Result<KeysendResponse> {
+        self.call(Endpoint::Keysend, serde_json::to_value(keysend_payload)?)
+    }
+
+    async fn create_charge(&self, charge: &Charge) -> crate::Result<FetchOneChargeResponse> {
+        self.call(Endpoint::CreateCharge, serde_json::to_value(charge)?)
+    }
+
+    async fn get_charges(&self) -> crate::Result<FetchChargesResponse> {
+        self.call(Endpoint::GetCharges, Value::Null)
+    }
+
+    async fn get_charge(&self, charge_id: &ChargeId) -> crate::Result<FetchOneChargeResponse> {
+        self.call(Endpoint::GetCharge, json!(charge_id))
+    }
+
+    async fn pay_gamertag(&self, payment: &GamertagPayment) -> crate::Result<GamertagPayResponse> {
+        self.call(Endpoint::PayGamertag, serde_json::to_value(payment)?)
+    }
+
+    async fn fetch_charge_from_gamertag(
+        &self,
+        payment: &GamertagPayment,
+    ) -> crate::Result<GamertagChargeResponse> {
+        self.call(
+            Endpoint::FetchChargeFromGamertag,
+            serde_json::to_value(payment)?,
+        )
+    }
+
+    async fn get_gamertag_tx(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> crate::Result<GamertagTxResponse> {
+        self.call(Endpoint::GetGamertagTx, json!(transaction_id))
+    }
+
+    async fn get_userid_by_gamertag(
+        &self,
+        gamertag: &str,
+    ) -> crate::Result<IdFromGamertagResponse> {
+        self.call(Endpoint::GetUseridByGamertag, json!(gamertag))
+    }
+
+    async fn get_gamertag_by_userid(
+        &self,
+        user_id: &UserId,
+    ) -> crate::Result<GamertagUserIdResponse> {
+        self.call(Endpoint::GetGamertagByUserid, json!(user_id))
+    }
+
+    async fn internal_transfer(
+        &self,
+        internal_transfer_payload: &InternalTransfer,
+    ) -> crate::Result<InternalTransferResponse> {
+        self.call(
+            Endpoint::InternalTransfer,
+            serde_json::to_value(internal_transfer_payload)?,
+        )
+    }
+
+    async fn pay_ln_address(&self, payment: &LnPayment) -> crate::Result<PayLnAddressResponse> {
+        self.call(Endpoint::PayLnAddress, serde_json::to_value(payment)?)
+    }
+
+    async fn fetch_charge_ln_address(
+        &self,
+        payment: &LnFetchCharge,
+    ) -> crate::Result<FetchLnChargeResponse> {
+        self.call(
+            Endpoint::FetchChargeLnAddress,
+            serde_json::to_value(payment)?,
+        )
+    }
+
+    async fn validate_ln_address(
+        &self,
+        lightning_address: &LnAddress,
+    ) -> crate::Result<ValidateLnAddrResponse> {
+        self.call(
+            Endpoint::ValidateLnAddress,
+            json!(lightning_address.address),
+        )
+    }
+
+    async fn pay_invoice(&self, payment: &Payment) -> crate::Result<PaymentInvoiceResponse> {
+        self.call(Endpoint::PayInvoice, serde_json::to_value(payment)?)
+    }
+
+    async fn get_payments(&self) -> crate::Result<FetchPaymentsResponse> {
+        self.call(Endpoint::GetPayments, Value::Null)
+    }
+
+    async fn get_payment(&self, payment_id: &PaymentId) -> crate::Result<FetchOnePaymentsResponse> {
+        self.call(Endpoint::GetPayment, json!(payment_id))
+    }
+
+    async fn get_is_supported_region_by_ip(&self, ip: &str) -> crate::Result<SupportedIpResponse> {
+        self.call(Endpoint::GetIsSupportedRegionByIp, json!(ip))
+    }
+
+    async fn get_prod_ips(&self) -> crate::Result<ProdIpsResponse> {
+        self.call(Endpoint::GetProdIps, Value::Null)
+    }
+
+    async fn get_btc_usd(&self) -> crate::Result<BtcToUsdResponse> {
+        self.call(Endpoint::GetBtcUsd, Value::Null)
+    }
+
+    async fn create_withdrawal_request(
+        &self,
+        withdrawal_request: &WithdrawalReqest,
+    ) -> crate::Result<CreateWithdrawalResponse> {
+        self.call(
+            Endpoint::CreateWithdrawalRequest,
+            serde_json::to_value(withdrawal_request)?,
+        )
+    }
+
+    async fn get_withdrawal_requests(&self) -> crate::Result<FetchWithdrawalsResponse> {
+        self.call(Endpoint::GetWithdrawalRequests, Value::Null)
+    }
+
+    async fn get_withdrawal_request(
+        &self,
+        withdrawal_id: &WithdrawalId,
+    ) -> crate::Result<FetchOneWithdrawalResponse> {
+        self.call(Endpoint::GetWithdrawalRequest, json!(withdrawal_id))
+    }
+
+    async fn pay_email(
+        &self,
+        email_payment_request: &EmailPaymentReqest,
+    ) -> crate::Result<EmailPaymentResponse> {
+        self.call(
+            Endpoint::PayEmail,
+            serde_json::to_value(email_payment_request)?,
+        )
+    }
+
+    async fn create_auth_url(&self, pkce: &PKCE) -> crate::Result<String> {
+        self.call(
+            Endpoint::CreateAuthUrl,
+            json!({ "challenge": pkce.challenge, "method": pkce.method.as_str() }),
+        )
+    }
+
+    async fn fetch_token(&self, code: &str, verifier: &str) -> crate::Result<FetchAccessTokenRes> {
+        self.call(
+            Endpoint::FetchToken,
+            json!({ "code": code, "verifier": verifier }),
+        )
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> crate::Result<FetchPostRes> {
+        self.call(Endpoint::RefreshToken, json!(refresh_token))
+    }
+
+    async fn fetch_user_data(&self, token: &str) -> crate::Result<StdResp<ZBDUserData>> {
+        self.call(Endpoint::FetchUserData, json!(token))
+    }
+
+    async fn fetch_user_wallet_data(
+        &self,
+        token: &str,
+    ) -> crate::Result<StdResp<ZBDUserWalletData>> {
+        self.call(Endpoint::FetchUserWalletData, json!(token))
+    }
+}
@@ -0,0 +1,6 @@
+mod fake;
+mod types;
+pub use fake::*;
+pub use types::*;
+#[cfg(test)]
+mod tests;
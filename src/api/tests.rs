@@ -0,0 +1,94 @@
+use super::*;
+use crate::{
+    charges::*,
+    errors::{ErrorMsg, ZebedeeError},
+    ids::ChargeId,
+    transport::MockTransport,
+    StdResp,
+};
+use serde_json::json;
+
+const CHARGE_ID: &str = "c6b1ca5b-0bc6-4e4d-a8e2-1bdd4d1f2a43";
+
+async fn charge_amount(api: &dyn ZebedeeApi, charge_id: &ChargeId) -> crate::Result<String> {
+    let charge = api.get_charge(charge_id).await?;
+    Ok(charge.data.map(|data| data.amount).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_fake_responds_and_records_calls() {
+    let fake = FakeZebedeeApi::new();
+    let charge = ChargesData {
+        id: CHARGE_ID.parse().unwrap(),
+        unit: "msats".to_owned(),
+        amount: "1000".to_owned(),
+        created_at: None,
+        internal_id: String::new(),
+        callback_url: String::new(),
+        description: String::new(),
+        expires_at: None,
+        confirmed_at: None,
+        status: "pending".to_owned(),
+        invoice: None,
+    };
+    fake.respond(
+        Endpoint::GetCharge,
+        &StdResp {
+            success: true,
+            data: Some(charge),
+            message: None,
+        },
+    )
+    .unwrap();
+
+    let charge_id = CHARGE_ID.parse().unwrap();
+    assert_eq!(charge_amount(&fake, &charge_id).await.unwrap(), "1000");
+    assert_eq!(charge_amount(&fake, &charge_id).await.unwrap(), "1000");
+
+    let calls = fake.calls_to(Endpoint::GetCharge);
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].args, json!(CHARGE_ID));
+
+    fake.create_charge(&Charge::default()).await.unwrap_err();
+    assert_eq!(fake.calls().len(), 3);
+    assert_eq!(fake.calls()[2].args["expiresIn"], 300);
+    fake.clear_calls();
+    assert_eq!(fake.call_count(Endpoint::GetCharge), 0);
+}
+
+#[tokio::test]
+async fn test_fake_one_shot_responses_come_first() {
+    let fake = FakeZebedeeApi::new();
+    fake.fail_once(Endpoint::GetPayments, "Rate limited");
+    fake.respond(
+        Endpoint::GetPayments,
+        &json!({ "success": true, "data": [] }),
+    )
+    .unwrap();
+
+    let err = fake.get_payments().await.unwrap_err();
+    assert!(matches!(err, ZebedeeError::Api(_)));
+    assert_eq!(err.to_string(), "Rate limited");
+
+    let payments = fake.get_payments().await.unwrap();
+    assert!(payments.data.unwrap().is_empty());
+    assert_eq!(fake.call_count(Endpoint::GetPayments), 2);
+}
+
+#[tokio::test]
+async fn test_fake_without_response() {
+    let fake = FakeZebedeeApi::new();
+    let err = fake.get_btc_usd().await.unwrap_err();
+    assert!(matches!(
+        err,
+        ZebedeeError::Msg(ErrorMsg::NoFakeResponse(Endpoint::GetBtcUsd))
+    ));
+}
+
+#[tokio::test]
+async fn test_client_behind_trait_object() {
+    let client = crate::ZebedeeClient::new("key").transport(MockTransport::new());
+    let api: Box<dyn ZebedeeApi> = Box::new(client);
+    let err = api.get_wallet_details().await.unwrap_err();
+    assert!(matches!(err, ZebedeeError::Transport(_)));
+}
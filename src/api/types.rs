@@ -0,0 +1,439 @@
+use crate::{
+    charges::*, email::*, gamertag::*, ids::*, internal_transfer::*, keysend::*, ln_address::*,
+    login_with_zbd::*, payments::*, utilities::*, wallet::*, withdrawal_request::*, StdResp,
+    ZebedeeClient,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Endpoints of the Zebedee REST API, one per [`ZebedeeApi`] method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    GetWalletDetails,
+    Keysend,
+    CreateCharge,
+    GetCharges,
+    GetCharge,
+    PayGamertag,
+    FetchChargeFromGamertag,
+    GetGamertagTx,
+    GetUseridByGamertag,
+    GetGamertagByUserid,
+    InternalTransfer,
+    PayLnAddress,
+    FetchChargeLnAddress,
+    ValidateLnAddress,
+    PayInvoice,
+    GetPayments,
+    GetPayment,
+    GetIsSupportedRegionByIp,
+    GetProdIps,
+    GetBtcUsd,
+    CreateWithdrawalRequest,
+    GetWithdrawalRequests,
+    GetWithdrawalRequest,
+    PayEmail,
+    CreateAuthUrl,
+    FetchToken,
+    RefreshToken,
+    FetchUserData,
+    FetchUserWalletData,
+}
+
+/// Every endpoint of the Zebedee REST API.
+///
+/// Implemented by [`ZebedeeClient`], services can depend on `dyn ZebedeeApi` and use
+/// [`FakeZebedeeApi`](crate::api::FakeZebedeeApi) in their unit tests.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait ZebedeeApi: Send + Sync {
+    // wallet
+    async fn get_wallet_details(&self) -> crate::Result<WalletInfoResponse>;
+
+    // keysend
+    async fn keysend(&self, keysend_payload: &Keysend) -> crate::Result<KeysendResponse>;
+
+    // charges
+    async fn create_charge(&self, charge: &Charge) -> crate::Result<FetchOneChargeResponse>;
+    async fn get_charges(&self) -> crate::Result<FetchChargesResponse>;
+    async fn get_charge(&self, charge_id: &ChargeId) -> crate::Result<FetchOneChargeResponse>;
+
+    // gamertag
+    async fn pay_gamertag(&self, payment: &GamertagPayment) -> crate::Result<GamertagPayResponse>;
+    async fn fetch_charge_from_gamertag(
+        &self,
+        payment: &GamertagPayment,
+    ) -> crate::Result<GamertagChargeResponse>;
+    async fn get_gamertag_tx(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> crate::Result<GamertagTxResponse>;
+    async fn get_userid_by_gamertag(&self, gamertag: &str)
+        -> crate::Result<IdFromGamertagResponse>;
+    async fn get_gamertag_by_userid(
+        &self,
+        user_id: &UserId,
+    ) -> crate::Result<GamertagUserIdResponse>;
+
+    // internal transfer
+    async fn internal_transfer(
+        &self,
+        internal_transfer_payload: &InternalTransfer,
+    ) -> crate::Result<InternalTransferResponse>;
+
+    // ln address
+    async fn pay_ln_address(&self, payment: &LnPayment) -> crate::Result<PayLnAddressResponse>;
+    async fn fetch_charge_ln_address(
+        &self,
+        payment: &LnFetchCharge,
+    ) -> crate::Result<FetchLnChargeResponse>;
+    async fn validate_ln_address(
+        &self,
+        lightning_address: &LnAddress,
+    ) -> crate::Result<ValidateLnAddrResponse>;
+
+    // payments
+    async fn pay_invoice(&self, payment: &Payment) -> crate::Result<PaymentInvoiceResponse>;
+    async fn get_payments(&self) -> crate::Result<FetchPaymentsResponse>;
+    async fn get_payment(&self, payment_id: &PaymentId) -> crate::Result<FetchOnePaymentsResponse>;
+
+    // utilities
+    async fn get_is_supported_region_by_ip(&self, ip: &str) -> crate::Result<SupportedIpResponse>;
+    async fn get_prod_ips(&self) -> crate::Result<ProdIpsResponse>;
+    async fn get_btc_usd(&self) -> crate::Result<BtcToUsdResponse>;
+
+    // withdrawal requests
+    async fn create_withdrawal_request(
+        &self,
+        withdrawal_request: &WithdrawalReqest,
+    ) -> crate::Result<CreateWithdrawalResponse>;
+    async fn get_withdrawal_requests(&self) -> crate::Result<FetchWithdrawalsResponse>;
+    async fn get_withdrawal_request(
+        &self,
+        withdrawal_id: &WithdrawalId,
+    ) -> crate::Result<FetchOneWithdrawalResponse>;
+
+    // email
+    async fn pay_email(
+        &self,
+        email_payment_request: &EmailPaymentReqest,
+    ) -> crate::Result<EmailPaymentResponse>;
+
+    // oauth
+    async fn create_auth_url(&self, pkce: &PKCE) -> crate::Result<String>;
+    async fn fetch_token(&self, code: &str, verifier: &str) -> crate::Result<FetchAccessTokenRes>;
+    async fn refresh_token(&self, refresh_token: &str) -> crate::Result<FetchPostRes>;
+    async fn fetch_user_data(&self, token: &str) -> crate::Result<StdResp<ZBDUserData>>;
+    async fn fetch_user_wallet_data(
+        &self,
+        token: &str,
+    ) -> crate::Result<StdResp<ZBDUserWalletData>>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ZebedeeApi for ZebedeeClient {
+    async fn get_wallet_details(&self) -> crate::Result<WalletInfoResponse> {
+        ZebedeeClient::get_wallet_details(self).await
+    }
+
+    async fn keysend(&self, keysend_payload: &Keysend) -> crate::Result<KeysendResponse> {
+        ZebedeeClient::keysend(self, keysend_payload).await
+    }
+
+    async fn create_charge(&self, charge: &Charge) -> crate::Result<FetchOneChargeResponse> {
+        ZebedeeClient::create_charge(self, charge).await
+    }
+
+    async fn get_charges(&self) -> crate::Result<FetchChargesResponse> {
+        ZebedeeClient::get_charges(self).await
+    }
+
+    async fn get_charge(&self, charge_id: &ChargeId) -> crate::Result<FetchOneChargeResponse> {
+        ZebedeeClient::get_charge(self, charge_id).await
+    }
+
+    async fn pay_gamertag(&self, payment: &GamertagPayment) -> crate::Result<GamertagPayResponse> {
+        ZebedeeClient::pay_gamertag(self, payment).await
+    }
+
+    async fn fetch_charge_from_gamertag(
+        &self,
+        payment: &GamertagPayment,
+    ) -> crate::Result<GamertagChargeResponse> {
+        ZebedeeClient::fetch_charge_from_gamertag(self, payment).await
+    }
+
+    async fn get_gamertag_tx(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> crate::Result<GamertagTxResponse> {
+        ZebedeeClient::get_gamertag_tx(self, transaction_id).await
+    }
+
+    async fn get_userid_by_gamertag(
+        &self,
+        gamertag: &str,
+    ) -> crate::Result<IdFromGamertagResponse> {
+        ZebedeeClient::get_userid_by_gamertag(self, gamertag).await
+    }
+
+    async fn get_gamertag_by_userid(
+        &self,
+        user_id: &UserId,
+    ) -> crate::Result<GamertagUserIdResponse> {
+        ZebedeeClient::get_gamertag_by_userid(self, user_id).await
+    }
+
+    async fn internal_transfer(
+        &self,
+        internal_transfer_payload: &InternalTransfer,
+    ) -> crate::Result<InternalTransferResponse> {
+        ZebedeeClient::internal_transfer(self, internal_transfer_payload).await
+    }
+
+    async fn pay_ln_address(&self, payment: &LnPayment) -> crate::Result<PayLnAddressResponse> {
+        ZebedeeClient::pay_ln_address(self, payment).await
+    }
+
+    async fn fetch_charge_ln_address(
+        &self,
+        payment: &LnFetchCharge,
+    ) -> crate::Result<FetchLnChargeResponse> {
+        ZebedeeClient::fetch_charge_ln_address(self, payment).await
+    }
+
+    async fn validate_ln_address(
+        &self,
+        lightning_address: &LnAddress,
+    ) -> crate::Result<ValidateLnAddrResponse> {
+        ZebedeeClient::validate_ln_address(self, lightning_address).await
+    }
+
+    async fn pay_invoice(&self, payment: &Payment) -> crate::Result<PaymentInvoiceResponse> {
+        ZebedeeClient::pay_invoice(self, payment).await
+    }
+
+    async fn get_payments(&self) -> crate::Result<FetchPaymentsResponse> {
+        ZebedeeClient::get_payments(self).await
+    }
+
+    async fn get_payment(&self, payment_id: &PaymentId) -> crate::Result<FetchOnePaymentsResponse> {
+        ZebedeeClient::get_payment(self, payment_id).await
+    }
+
+    async fn get_is_supported_region_by_ip(&self, ip: &str) -> crate::Result<SupportedIpResponse> {
+        ZebedeeClient::get_is_supported_region_by_ip(self, ip).await
+    }
+
+    async fn get_prod_ips(&self) -> crate::Result<ProdIpsResponse> {
+        ZebedeeClient::get_prod_ips(self).await
+    }
+
+    async fn get_btc_usd(&self) -> crate::Result<BtcToUsdResponse> {
+        ZebedeeClient::get_btc_usd(self).await
+    }
+
+    async fn create_withdrawal_request(
+        &self,
+        withdrawal_request: &WithdrawalReqest,
+    ) -> crate::Result<CreateWithdrawalResponse> {
+        ZebedeeClient::create_withdrawal_request(self, withdrawal_request).await
+    }
+
+    async fn get_withdrawal_requests(&self) -> crate::Result<FetchWithdrawalsResponse> {
+        ZebedeeClient::get_withdrawal_requests(self).await
+    }
+
+    async fn get_withdrawal_request(
+        &self,
+        withdrawal_id: &WithdrawalId,
+    ) -> crate::Result<FetchOneWithdrawalResponse> {
+        ZebedeeClient::get_withdrawal_request(self, withdrawal_id).await
+    }
+
+    async fn pay_email(
+        &self,
+        email_payment_request: &EmailPaymentReqest,
+    ) -> crate::Result<EmailPaymentResponse> {
+        ZebedeeClient::pay_email(self, email_payment_request).await
+    }
+
+    async fn create_auth_url(&self, pkce: &PKCE) -> crate::Result<String> {
+        ZebedeeClient::create_auth_url(self, pkce).await
+    }
+
+    async fn fetch_token(&self, code: &str, verifier: &str) -> crate::Result<FetchAccessTokenRes> {
+        ZebedeeClient::fetch_token(self, code, verifier).await
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> crate::Result<FetchPostRes> {
+        ZebedeeClient::refresh_token(self, refresh_token).await
+    }
+
+    async fn fetch_user_data(&self, token: &str) -> crate::Result<StdResp<ZBDUserData>> {
+        ZebedeeClient::fetch_user_data(self, token).await
+    }
+
+    async fn fetch_user_wallet_data(
+        &self,
+        token: &str,
+    ) -> crate::Result<StdResp<ZBDUserWalletData>> {
+        ZebedeeClient::fetch_user_wallet_data(self, token).await
+    }
+}
+
+/// Lets helpers share one client, or a [`FakeZebedeeApi`](crate::api::FakeZebedeeApi) the
+/// test keeps a handle on, as `Arc<dyn ZebedeeApi>` or `Arc<T>`
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T: ZebedeeApi + ?Sized> ZebedeeApi for Arc<T> {
+    async fn get_wallet_details(&self) -> crate::Result<WalletInfoResponse> {
+        (**self).get_wallet_details().await
+    }
+
+    async fn keysend(&self, keysend_payload: &Keysend) -> crate::Result<KeysendResponse> {
+        (**self).keysend(keysend_payload).await
+    }
+
+    async fn create_charge(&self, charge: &Charge) -> crate::Result<FetchOneChargeResponse> {
+        (**self).create_charge(charge).await
+    }
+
+    async fn get_charges(&self) -> crate::Result<FetchChargesResponse> {
+        (**self).get_charges().await
+    }
+
+    async fn get_charge(&self, charge_id: &ChargeId) -> crate::Result<FetchOneChargeResponse> {
+        (**self).get_charge(charge_id).await
+    }
+
+    async fn pay_gamertag(&self, payment: &GamertagPayment) -> crate::Result<GamertagPayResponse> {
+        (**self).pay_gamertag(payment).await
+    }
+
+    async fn fetch_charge_from_gamertag(
+        &self,
+        payment: &GamertagPayment,
+    ) -> crate::Result<GamertagChargeResponse> {
+        (**self).fetch_charge_from_gamertag(payment).await
+    }
+
+    async fn get_gamertag_tx(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> crate::Result<GamertagTxResponse> {
+        (**self).get_gamertag_tx(transaction_id).await
+    }
+
+    async fn get_userid_by_gamertag(
+        &self,
+        gamertag: &str,
+    ) -> crate::Result<IdFromGamertagResponse> {
+        (**self).get_userid_by_gamertag(gamertag).await
+    }
+
+    async fn get_gamertag_by_userid(
+        &self,
+        user_id: &UserId,
+    ) -> crate::Result<GamertagUserIdResponse> {
+        (**self).get_gamertag_by_userid(user_id).await
+    }
+
+    async fn internal_transfer(
+        &self,
+        internal_transfer_payload: &InternalTransfer,
+    ) -> crate::Result<InternalTransferResponse> {
+        (**self).internal_transfer(internal_transfer_payload).await
+    }
+
+    async fn pay_ln_address(&self, payment: &LnPayment) -> crate::Result<PayLnAddressResponse> {
+        (**self).pay_ln_address(payment).await
+    }
+
+    async fn fetch_charge_ln_address(
+        &self,
+        payment: &LnFetchCharge,
+    ) -> crate::Result<FetchLnChargeResponse> {
+        (**self).fetch_charge_ln_address(payment).await
+    }
+
+    async fn validate_ln_address(
+        &self,
+        lightning_address: &LnAddress,
+    ) -> crate::Result<ValidateLnAddrResponse> {
+        (**self).validate_ln_address(lightning_address).await
+    }
+
+    async fn pay_invoice(&self, payment: &Payment) -> crate::Result<PaymentInvoiceResponse> {
+        (**self).pay_invoice(payment).await
+    }
+
+    async fn get_payments(&self) -> crate::Result<FetchPaymentsResponse> {
+        (**self).get_payments().await
+    }
+
+    async fn get_payment(&self, payment_id: &PaymentId) -> crate::Result<FetchOnePaymentsResponse> {
+        (**self).get_payment(payment_id).await
+    }
+
+    async fn get_is_supported_region_by_ip(&self, ip: &str) -> crate::Result<SupportedIpResponse> {
+        (**self).get_is_supported_region_by_ip(ip).await
+    }
+
+    async fn get_prod_ips(&self) -> crate::Result<ProdIpsResponse> {
+        (**self).get_prod_ips().await
+    }
+
+    async fn get_btc_usd(&self) -> crate::Result<BtcToUsdResponse> {
+        (**self).get_btc_usd().await
+    }
+
+    async fn create_withdrawal_request(
+        &self,
+        withdrawal_request: &WithdrawalReqest,
+    ) -> crate::Result<CreateWithdrawalResponse> {
+        (**self).create_withdrawal_request(withdrawal_request).await
+    }
+
+    async fn get_withdrawal_requests(&self) -> crate::Result<FetchWithdrawalsResponse> {
+        (**self).get_withdrawal_requests().await
+    }
+
+    async fn get_withdrawal_request(
+        &self,
+        withdrawal_id: &WithdrawalId,
+    ) -> crate::Result<FetchOneWithdrawalResponse> {
+        (**self).get_withdrawal_request(withdrawal_id).await
+    }
+
+    async fn pay_email(
+        &self,
+        email_payment_request: &EmailPaymentReqest,
+    ) -> crate::Result<EmailPaymentResponse> {
+        (**self).pay_email(email_payment_request).await
+    }
+
+    async fn create_auth_url(&self, pkce: &PKCE) -> crate::Result<String> {
+        (**self).create_auth_url(pkce).await
+    }
+
+    async fn fetch_token(&self, code: &str, verifier: &str) -> crate::Result<FetchAccessTokenRes> {
+        (**self).fetch_token(code, verifier).await
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> crate::Result<FetchPostRes> {
+        (**self).refresh_token(refresh_token).await
+    }
+
+    async fn fetch_user_data(&self, token: &str) -> crate::Result<StdResp<ZBDUserData>> {
+        (**self).fetch_user_data(token).await
+    }
+
+    async fn fetch_user_wallet_data(
+        &self,
+        token: &str,
+    ) -> crate::Result<StdResp<ZBDUserWalletData>> {
+        (**self).fetch_user_wallet_data(token).await
+    }
+}
@@ -0,0 +1,206 @@
+use super::*;
+use crate::{
+    destination::PaymentDestination, spend_policy::SpendPolicy, transport::MockTransport,
+    ZebedeeClient,
+};
+
+fn tournament() -> BatchPayout {
+    BatchPayout::new("tournament-42")
+        .recipient(
+            PaymentDestination::Gamertag(String::from("alice")),
+            "5000",
+            "1st",
+        )
+        .recipient(
+            PaymentDestination::LnAddress(String::from("bob@zbd.gg")),
+            "3000",
+            "2nd",
+        )
+        .recipient(
+            PaymentDestination::Gamertag(String::from("alice")),
+            "5000",
+            "bonus",
+        )
+        .concurrency(2)
+}
+
+#[tokio::test]
+async fn test_internal_ids_are_deterministic() {
+    let ids = tournament().internal_ids();
+    assert_eq!(ids, tournament().internal_ids());
+    assert_eq!(ids.len(), 3);
+    assert_eq!(ids[0].len(), 32);
+    assert_ne!(ids[0], ids[2]);
+
+    let mut reordered = tournament();
+    reordered.recipients.swap(0, 1);
+    let reordered_ids = reordered.internal_ids();
+    assert_eq!(reordered_ids[1], ids[0]);
+    assert_eq!(reordered_ids[0], ids[1]);
+
+    let other = BatchPayout::new("tournament-43").recipient(
+        PaymentDestination::Gamertag(String::from("alice")),
+        "5000",
+        "1st",
+    );
+    assert_ne!(other.internal_ids()[0], ids[0]);
+}
+
+#[tokio::test]
+async fn test_batch_continues_past_failures() {
+    let zebedee_client = ZebedeeClient::new("apikey")
+        .transport(MockTransport::new())
+        .spend_policy(SpendPolicy::new().max_per_transaction(4000));
+
+    let batch = tournament();
+    let mut seen = PayoutReport::default();
+    let report = zebedee_client
+        .resume_batch_payout(&batch, PayoutReport::default(), |r| {
+            if !matches!(r.outcome, PayoutOutcome::Pending) {
+                assert!(seen.pending().any(|p| p.internal_id == r.internal_id));
+            }
+            seen.insert(r.clone())
+        })
+        .await;
+
+    assert_eq!(report.batch_id, "tournament-42");
+    assert_eq!(seen.results.len(), 3);
+    assert_eq!(seen.pending().count(), 0);
+    // both alices are over the limit, bob passes it and hits the failing transport
+    assert_eq!(report.failed().count(), 2);
+    assert_eq!(report.unknown().count(), 1);
+    assert_eq!(
+        report
+            .results
+            .iter()
+            .map(|r| &r.internal_id)
+            .collect::<Vec<_>>(),
+        batch.internal_ids().iter().collect::<Vec<_>>()
+    );
+    match &report.results[0].outcome {
+        PayoutOutcome::Failed(e) => assert!(e.contains("Spend limit exceeded")),
+        outcome => panic!("unexpected outcome {outcome:?}"),
+    }
+
+    let json = serde_json::to_string(&report).unwrap();
+    let restored: PayoutReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.results.len(), 3);
+    assert!(!restored.is_paid(&batch.internal_ids()[0]));
+
+    // the unknown payout is left alone, the failed ones are sent again
+    let mut retried = Vec::new();
+    zebedee_client
+        .resume_batch_payout(&batch, restored, |r| {
+            if !matches!(r.outcome, PayoutOutcome::Pending) {
+                retried.push(r.internal_id.clone())
+            }
+        })
+        .await;
+    let ids = batch.internal_ids();
+    assert_eq!(retried.len(), 2);
+    assert!(!retried.contains(&ids[1]));
+}
+
+#[tokio::test]
+async fn test_every_destination_sends_the_internal_id() {
+    let transport = MockTransport::new();
+    transport.respond_always(400, r#"{"success":false,"message":"Insufficient balance"}"#);
+    let zebedee_client = ZebedeeClient::new("apikey").transport(transport.clone());
+
+    let pubkey = format!("02{}", "ab".repeat(32));
+    let batch = BatchPayout::new("season-1")
+        .recipient(PaymentDestination::Gamertag("alice".into()), "5000", "")
+        .recipient(
+            PaymentDestination::LnAddress("bob@zbd.gg".into()),
+            "5000",
+            "",
+        )
+        .recipient(
+            PaymentDestination::Email("carol@example.com".into()),
+            "5000",
+            "",
+        )
+        .recipient(PaymentDestination::NodePubkey(pubkey), "5000", "")
+        .concurrency(1);
+    let report = zebedee_client.batch_payout(&batch).await;
+    assert_eq!(report.failed().count(), 4);
+    assert_eq!(transport.requests().len(), 4);
+
+    let mut sent = transport
+        .requests()
+        .iter()
+        .map(|r| {
+            serde_json::from_slice::<serde_json::Value>(&r.body).unwrap()["internalId"].clone()
+        })
+        .collect::<Vec<_>>();
+    sent.sort_by_key(|id| id.to_string());
+    let mut ids = batch.internal_ids();
+    ids.sort();
+    assert_eq!(sent, ids);
+}
+
+#[tokio::test]
+async fn test_server_errors_and_empty_responses_are_unknown() {
+    let transport = MockTransport::new();
+    transport.respond(502, r#"{"success":false,"message":"Bad gateway"}"#);
+    transport.respond(200, r#"{"success":true,"message":"sent"}"#);
+    let zebedee_client = ZebedeeClient::new("apikey").transport(transport);
+
+    let batch = BatchPayout::new("season-1")
+        .recipient(PaymentDestination::Gamertag("alice".into()), "5000", "")
+        .recipient(
+            PaymentDestination::LnAddress("bob@zbd.gg".into()),
+            "5000",
+            "",
+        )
+        .concurrency(1);
+    let report = zebedee_client.batch_payout(&batch).await;
+    assert_eq!(report.unknown().count(), 2);
+    assert_eq!(report.failed().count(), 0);
+}
+
+#[tokio::test]
+async fn test_resume_looks_up_pending_payouts() {
+    let batch = tournament();
+    let ids = batch.internal_ids();
+    let mut report = PayoutReport::new("tournament-42");
+    for (id, recipient) in ids.iter().zip(&batch.recipients).take(2) {
+        report.insert(PayoutResult {
+            internal_id: id.clone(),
+            recipient: recipient.clone(),
+            outcome: PayoutOutcome::Pending,
+        });
+    }
+    let json = serde_json::to_string(&report).unwrap();
+    assert!(json.contains(r#"{"status":"pending"}"#));
+    let report: PayoutReport = serde_json::from_str(&json).unwrap();
+
+    let transport = MockTransport::new();
+    transport.respond(
+        200,
+        format!(
+            r#"{{"success":true,"message":"ok","data":[{{
+                "id":"c3b5d2a1-0000-4000-8000-000000000001","fee":"0","unit":"msats",
+                "amount":"5000","invoice":"lnbc1","preimage":null,"internalId":"{}",
+                "processedAt":null,"confirmedAt":null,"description":"","status":"completed"
+            }}]}}"#,
+            ids[0]
+        ),
+    );
+    let zebedee_client = ZebedeeClient::new("apikey").transport(transport.clone());
+
+    let report = zebedee_client
+        .resume_batch_payout(&batch, report, |_| {})
+        .await;
+    assert!(report.is_paid(&ids[0]));
+    assert!(matches!(
+        report.get(&ids[1]).unwrap().outcome,
+        PayoutOutcome::Unknown(_)
+    ));
+    // only the lookup and the payout that never started went out
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].url.ends_with("/v0/payments"));
+    let sent: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert_eq!(sent["internalId"], ids[2].as_str());
+}
@@ -0,0 +1,291 @@
+use crate::{destination::*, errors::ZebedeeError, ZebedeeClient};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, sync::Mutex};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayoutRecipient {
+    pub destination: PaymentDestination,
+    /// Amount in millisatoshis, ignored for invoices
+    pub amount: String,
+    pub comment: String,
+}
+
+/// Result of a single payout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", content = "data", rename_all = "snake_case")]
+pub enum PayoutOutcome {
+    /// About to be sent. A resumed batch looks the internal id up in `get_payments`: a
+    /// payout found there is `Paid`, any other becomes `Unknown`.
+    Pending,
+    Paid(Box<PaymentOutcome>),
+    /// Rejected by local checks or a 4xx answer before any money moved, retried when the
+    /// batch is resumed
+    Failed(String),
+    /// The request may have reached the API, e.g. a timeout or an unreadable response.
+    /// Never retried automatically: check the transactions for the internal id, then
+    /// `insert` a `Failed` result to pay it again.
+    Unknown(String),
+}
+
+impl PayoutOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, PayoutOutcome::Paid(_))
+    }
+
+    fn from_error(e: ZebedeeError) -> Self {
+        if e.is_definitely_not_sent() {
+            PayoutOutcome::Failed(e.to_string())
+        } else {
+            PayoutOutcome::Unknown(e.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutResult {
+    pub internal_id: String,
+    pub recipient: PayoutRecipient,
+    pub outcome: PayoutOutcome,
+}
+
+/// Per-recipient results of a [`BatchPayout`]. Persist it while the batch runs and hand it
+/// back to `resume_batch_payout` to skip the payouts that already went through or may have.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayoutReport {
+    pub batch_id: String,
+    pub results: Vec<PayoutResult>,
+}
+
+impl PayoutReport {
+    pub fn new<T: Into<String>>(batch_id: T) -> Self {
+        Self {
+            batch_id: batch_id.into(),
+            results: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, internal_id: &str) -> Option<&PayoutResult> {
+        self.results.iter().find(|r| r.internal_id == internal_id)
+    }
+
+    pub fn is_paid(&self, internal_id: &str) -> bool {
+        self.get(internal_id)
+            .map(|r| r.outcome.is_success())
+            .unwrap_or(false)
+    }
+
+    /// Not attempted yet or definitely failed, the payouts a resumed batch sends
+    pub fn needs_retry(&self, internal_id: &str) -> bool {
+        self.get(internal_id)
+            .map(|r| matches!(r.outcome, PayoutOutcome::Failed(_)))
+            .unwrap_or(true)
+    }
+
+    pub fn succeeded(&self) -> impl Iterator<Item = &PayoutResult> {
+        self.results.iter().filter(|r| r.outcome.is_success())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &PayoutResult> {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, PayoutOutcome::Failed(_)))
+    }
+
+    /// Payouts sent when the batch stopped, see [`PayoutOutcome::Pending`]
+    pub fn pending(&self) -> impl Iterator<Item = &PayoutResult> {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, PayoutOutcome::Pending))
+    }
+
+    /// Payouts that may or may not have gone out, see [`PayoutOutcome::Unknown`]
+    pub fn unknown(&self) -> impl Iterator<Item = &PayoutResult> {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, PayoutOutcome::Unknown(_)))
+    }
+
+    /// Adds a result, replacing an earlier result for the same internal id
+    pub fn insert(&mut self, result: PayoutResult) {
+        match self
+            .results
+            .iter_mut()
+            .find(|r| r.internal_id == result.internal_id)
+        {
+            Some(existing) => *existing = result,
+            None => self.results.push(result),
+        }
+    }
+}
+
+/// A list of payouts run with bounded concurrency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPayout {
+    pub batch_id: String,
+    pub recipients: Vec<PayoutRecipient>,
+    pub concurrency: usize,
+}
+
+impl BatchPayout {
+    pub fn new<T: Into<String>>(batch_id: T) -> Self {
+        Self {
+            batch_id: batch_id.into(),
+            recipients: Vec::new(),
+            concurrency: 8,
+        }
+    }
+
+    pub fn recipient<A, C>(mut self, destination: PaymentDestination, amount: A, comment: C) -> Self
+    where
+        A: Into<String>,
+        C: Into<String>,
+    {
+        self.recipients.push(PayoutRecipient {
+            destination,
+            amount: amount.into(),
+            comment: comment.into(),
+        });
+        self
+    }
+
+    /// Max number of payouts in flight, defaults to 8
+    pub fn concurrency(self, concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Deterministic internal id of every recipient, in batch order.
+    /// Ids only depend on the batch id, the destination, the amount and how often that same
+    /// destination and amount appeared before, so reordering a batch keeps its ids stable.
+    pub fn internal_ids(&self) -> Vec<String> {
+        let mut seen: HashMap<(&PaymentDestination, &str), u32> = HashMap::new();
+        self.recipients
+            .iter()
+            .map(|recipient| {
+                let key = (&recipient.destination, recipient.amount.as_str());
+                let occurrence = seen.entry(key).or_default();
+                let id = payout_id(&self.batch_id, recipient, *occurrence);
+                *occurrence += 1;
+                id
+            })
+            .collect()
+    }
+
+    /// Reports each payout as `Pending` through `on_result` right before sending it, then
+    /// again with its outcome
+    pub(crate) async fn run<F>(
+        &self,
+        client: &ZebedeeClient,
+        mut report: PayoutReport,
+        mut on_result: F,
+    ) -> PayoutReport
+    where
+        F: FnMut(&PayoutResult),
+    {
+        report.batch_id = self.batch_id.clone();
+        let ids = self.internal_ids();
+
+        Self::settle_pending(client, &mut report, &mut on_result).await;
+
+        let pending = ids
+            .iter()
+            .zip(&self.recipients)
+            .filter(|(id, _)| report.needs_retry(id))
+            .collect::<Vec<_>>();
+
+        let on_result = Mutex::new(on_result);
+        let notify =
+            |result: &PayoutResult| (on_result.lock().unwrap_or_else(|e| e.into_inner()))(result);
+
+        let mut results = stream::iter(pending)
+            .map(|(id, recipient)| {
+                let notify = &notify;
+                async move {
+                    notify(&PayoutResult {
+                        internal_id: id.clone(),
+                        recipient: recipient.clone(),
+                        outcome: PayoutOutcome::Pending,
+                    });
+                    PayoutResult {
+                        internal_id: id.clone(),
+                        recipient: recipient.clone(),
+                        outcome: match client
+                            .pay_with_internal_id(
+                                &recipient.destination,
+                                &recipient.amount,
+                                &recipient.comment,
+                                Some(id.clone()),
+                            )
+                            .await
+                        {
+                            Ok(outcome) => PayoutOutcome::Paid(Box::new(outcome)),
+                            Err(e) => PayoutOutcome::from_error(e),
+                        },
+                    }
+                }
+            })
+            .buffer_unordered(self.concurrency);
+
+        while let Some(result) = results.next().await {
+            notify(&result);
+            report.insert(result);
+        }
+
+        let order: HashMap<&String, usize> =
+            ids.iter().enumerate().map(|(i, id)| (id, i)).collect();
+        report
+            .results
+            .sort_by_key(|r| order.get(&r.internal_id).copied().unwrap_or(usize::MAX));
+        report
+    }
+
+    /// Resolves payouts left `Pending` by an interrupted run with `get_payments`. They stay
+    /// `Pending`, and are not sent, while the payments cannot be listed.
+    async fn settle_pending<F>(client: &ZebedeeClient, report: &mut PayoutReport, on_result: &mut F)
+    where
+        F: FnMut(&PayoutResult),
+    {
+        let pending = report.pending().cloned().collect::<Vec<_>>();
+        if pending.is_empty() {
+            return;
+        }
+        let Ok(payments) = client.get_payments().await else {
+            return;
+        };
+        let mut payments = payments.data.unwrap_or_default();
+
+        for mut result in pending {
+            result.outcome = match payments
+                .iter()
+                .position(|p| p.internal_id.as_deref() == Some(result.internal_id.as_str()))
+            {
+                Some(i) => {
+                    PayoutOutcome::Paid(Box::new(PaymentOutcome::Invoice(payments.remove(i))))
+                }
+                None => PayoutOutcome::Unknown(String::from(
+                    "sent when the batch stopped, not found in the payments",
+                )),
+            };
+            on_result(&result);
+            report.insert(result);
+        }
+    }
+}
+
+fn payout_id(batch_id: &str, recipient: &PayoutRecipient, occurrence: u32) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(batch_id);
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(&recipient.destination).unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(&recipient.amount);
+    hasher.update(occurrence.to_be_bytes());
+    hasher.finalize()[..16]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
@@ -1,13 +1,9 @@
 use super::*;
 use crate::ZebedeeClient;
-use std::env;
 
 #[tokio::test]
 async fn test_create_charge() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
     let charge = Charge {
         amount: String::from("1000"),
         ..Default::default()
@@ -19,20 +15,14 @@ async fn test_create_charge() {
 
 #[tokio::test]
 async fn test_get_charges() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let r = zebedee_client.get_charges().await.unwrap();
     assert!(r.success);
 }
 #[tokio::test]
 async fn test_get_charge() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let charge = Charge {
         amount: String::from("1000"),
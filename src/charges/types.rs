@@ -1,4 +1,4 @@
-use crate::StdResp;
+use crate::{ids::ChargeId, StdResp};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -11,9 +11,9 @@ pub struct InvoiceData {
     pub uri: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ChargesData {
-    pub id: String,
+    pub id: ChargeId,
     pub unit: String,
     pub amount: String,
     #[serde(rename = "createdAt")]
@@ -32,7 +32,7 @@ pub struct ChargesData {
 }
 
 /// Use this struct to create a well crafted json body for your charge requests
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Charge {
     #[serde(rename = "expiresIn")]
     pub expires_in: u32,
@@ -0,0 +1,77 @@
+use super::*;
+use crate::ZebedeeClient;
+use std::collections::HashMap;
+
+#[test]
+fn test_parse_environment() {
+    assert_eq!(
+        "production".parse::<Environment>().unwrap(),
+        Environment::Production
+    );
+    assert_eq!(
+        "Staging".parse::<Environment>().unwrap(),
+        Environment::Sandbox
+    );
+    assert_eq!(
+        "https://api.zebedee.io/".parse::<Environment>().unwrap(),
+        Environment::Production
+    );
+
+    let custom: Environment = "http://localhost:8080/".parse().unwrap();
+    assert_eq!(custom.url(), "http://localhost:8080");
+
+    assert!("api.zebedee.io".parse::<Environment>().is_err());
+    assert!("ftp://api.zebedee.io".parse::<Environment>().is_err());
+}
+
+#[test]
+fn test_client_from_lookup() {
+    let vars: HashMap<&str, &str> = HashMap::from([
+        ("ZBD_API_KEY", "some-api-key"),
+        ("ZBD_ENV", "sandbox"),
+        ("ZBD_OAUTH_CLIENT_ID", "some-client-id"),
+    ]);
+    let lookup = |name: &str| vars.get(name).map(|v| v.to_string());
+
+    let err = ZebedeeClient::from_lookup(lookup).unwrap_err();
+    assert!(err.to_string().contains("ZBD_OAUTH_SECRET"));
+
+    let err = ZebedeeClient::from_lookup(|_| None).unwrap_err();
+    assert!(err.to_string().contains("ZBD_API_KEY"));
+
+    let client = ZebedeeClient::from_lookup(|name| {
+        (name == "ZBD_API_KEY").then(|| "some-api-key".to_owned())
+    })
+    .unwrap();
+    assert_eq!(client.domain, PRODUCTION_URL);
+}
+
+#[test]
+fn test_config_profiles() {
+    let config: ZebedeeConfig = r#"
+        default_profile = "main"
+
+        [profiles.main]
+        apikey = "main-api-key"
+
+        [profiles.local]
+        apikey = "local-api-key"
+        environment = "http://localhost:3000"
+        oauth = { client_id = "id", secret = "secret", redirect_uri = "http://localhost/cb" }
+    "#
+    .parse()
+    .unwrap();
+
+    let main = config.default_profile().unwrap();
+    assert_eq!(main.apikey.expose_secret(), "main-api-key");
+    assert_eq!(main.environment, Environment::Production);
+
+    let local = config.client("local").unwrap();
+    assert_eq!(local.domain, "http://localhost:3000");
+    assert_eq!(local.oauth.scope, "user");
+
+    assert!(config.profile("missing").is_err());
+    assert!("[profiles.bad]\napikey = 1"
+        .parse::<ZebedeeConfig>()
+        .is_err());
+}
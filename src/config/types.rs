@@ -0,0 +1,138 @@
+use crate::{errors::ErrorMsg, secret::Secret, ZebedeeClient, ZebedeeOauth};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::HashMap, fmt::Display, path::Path, str::FromStr};
+use url::Url;
+
+pub const PRODUCTION_URL: &str = "https://api.zebedee.io";
+pub const SANDBOX_URL: &str = "https://sandbox-api.zebedee.io";
+
+/// Zebedee REST API environment the client talks to
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Environment {
+    #[default]
+    Production,
+    /// Also accepted as `staging` when parsed from a string
+    Sandbox,
+    Custom(Url),
+}
+
+impl Environment {
+    /// Base url of the environment without a trailing slash
+    pub fn url(&self) -> &str {
+        match self {
+            Environment::Production => PRODUCTION_URL,
+            Environment::Sandbox => SANDBOX_URL,
+            Environment::Custom(url) => url.as_str().trim_end_matches('/'),
+        }
+    }
+}
+
+impl FromStr for Environment {
+    type Err = ErrorMsg;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.to_ascii_lowercase().as_str() {
+            "" | "production" | "prod" => Ok(Environment::Production),
+            "sandbox" | "staging" => Ok(Environment::Sandbox),
+            _ => {
+                let url =
+                    Url::parse(s).map_err(|e| ErrorMsg::BadEnvironment(format!("{s}: {e}")))?;
+                if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+                    return Err(ErrorMsg::BadEnvironment(s.to_owned()));
+                }
+                Ok(match url.as_str().trim_end_matches('/') {
+                    PRODUCTION_URL => Environment::Production,
+                    SANDBOX_URL => Environment::Sandbox,
+                    _ => Environment::Custom(url),
+                })
+            }
+        }
+    }
+}
+
+impl Display for Environment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Environment::Production => f.write_str("production"),
+            Environment::Sandbox => f.write_str("sandbox"),
+            Environment::Custom(_) => f.write_str(self.url()),
+        }
+    }
+}
+
+impl Serialize for Environment {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Environment {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A named set of credentials inside a [`ZebedeeConfig`] file
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    pub apikey: Secret,
+    #[serde(default)]
+    pub environment: Environment,
+    pub oauth: Option<ZebedeeOauth>,
+}
+
+/// TOML config file holding one or more named profiles
+///
+/// ```toml
+/// default_profile = "main"
+///
+/// [profiles.main]
+/// apikey = "..."
+/// environment = "production"
+///
+/// [profiles.staging]
+/// apikey = "..."
+/// environment = "https://my-proxy.example.com"
+/// oauth = { client_id = "...", secret = "...", redirect_uri = "...", state = "...", scope = "user" }
+/// ```
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ZebedeeConfig {
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+impl ZebedeeConfig {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        contents.parse()
+    }
+
+    /// Looks up a profile by name
+    pub fn profile(&self, name: &str) -> crate::Result<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| ErrorMsg::UnknownProfile(name.to_owned()).into())
+    }
+
+    /// The profile named by `default_profile`, falling back to a profile called `default`
+    pub fn default_profile(&self) -> crate::Result<&Profile> {
+        self.profile(self.default_profile.as_deref().unwrap_or("default"))
+    }
+
+    /// Builds a client from the named profile
+    pub fn client(&self, name: &str) -> crate::Result<ZebedeeClient> {
+        Ok(ZebedeeClient::from_profile(self.profile(name)?))
+    }
+}
+
+impl FromStr for ZebedeeConfig {
+    type Err = crate::errors::ZebedeeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
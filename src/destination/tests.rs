@@ -0,0 +1,131 @@
+use super::*;
+use crate::{lnurl::encode_lnurl, transport::MockTransport, ZebedeeClient};
+
+const INVOICE: &str = "lnbc120n1p0tdjwmpp5ycws0d788cjeqp9rn2wwxfymrekj9n80wy2yrk66tuu3ga5wukfsdzq2pshjmt9de6zqen0wgsrzv3qwp5hsetvwvsxzapqwdshgmmndp5hxtnsd3skxefwxqzjccqp2sp5vnsvmjlu6hrfegcdjs47njrga36g3x45wfmqjjjlerwgagj62yysrzjq2v4aw4gy7m93en32dcaplym056zezcljdjshyk8yakwtsp2h4yvcz9atuqqhtsqqqqqqqlgqqqqqqgqjq9qy9qsqhykfacrdy06cuyegvt4p50su53qwgrqn5jf6d83fd0upsa4frpxqnm2zl323zuvmz5ypv9gh9nr3jav6u2ccwkpd56h3n6l3ja5q7wgpxudlv4";
+
+#[tokio::test]
+async fn test_parse_destinations() {
+    let parse = |s: &str| s.parse::<PaymentDestination>().unwrap();
+
+    assert_eq!(
+        parse(INVOICE),
+        PaymentDestination::Invoice(INVOICE.to_owned())
+    );
+    assert_eq!(
+        parse(&format!("LIGHTNING:{}", INVOICE.to_uppercase())),
+        PaymentDestination::Invoice(INVOICE.to_owned())
+    );
+    assert_eq!(
+        parse("lightning:LNURL1DP68GURN8GHJ7UM9WFMXJCM99E3K7MF0V9CXJ0M385EKVCENXC6R2C35XVUKXEFCV5MKVV34X5EKZD3EV56NYD3HXQURZEPEXEJXXEPNXSCRVWFNV9NXZCN9XQ6XYEFHVGCXXCMYXYMNSERXFQ5FNS"),
+        PaymentDestination::Lnurl(String::from("lnurl1dp68gurn8ghj7um9wfmxjcm99e3k7mf0v9cxj0m385ekvcenxc6r2c35xvukxefcv5mkvv34x5ekzd3ev56nyd3hxqurzepexejxxepnxscrvwfnv9nxzcn9xq6xyefhvgcxxcmyxymnserxfq5fns"))
+    );
+    assert_eq!(
+        parse("Andre@ZBD.gg"),
+        PaymentDestination::LnAddress(String::from("andre@zbd.gg"))
+    );
+    assert_eq!(
+        parse("mailto:someone@example.com"),
+        PaymentDestination::Email(String::from("someone@example.com"))
+    );
+    assert_eq!(
+        parse("@miketwenty1"),
+        PaymentDestination::Gamertag(String::from("miketwenty1"))
+    );
+    assert_eq!(
+        parse(" @miketwenty1 "),
+        PaymentDestination::Gamertag(String::from("miketwenty1"))
+    );
+    assert_eq!(
+        parse("033e514ff30be0ea421f9512da0ed1aea52ea541275654d034bde3470a61269285"),
+        PaymentDestination::NodePubkey(String::from(
+            "033e514ff30be0ea421f9512da0ed1aea52ea541275654d034bde3470a61269285"
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_parse_bad_destinations() {
+    for bad in [
+        "",
+        "@",
+        "a@b",
+        "two words",
+        "mailto:nobody",
+        "@bad tag",
+        "x@.com",
+        "miketwenty1",
+        "lnbc120n1p0tdjwmpp5",
+        "lntbs1short",
+        "033e514ff30be0ea421f9512da0ed1aea52ea541275654d034bde3470a6126",
+        "deadbeef",
+    ] {
+        assert!(bad.parse::<PaymentDestination>().is_err(), "{bad}");
+    }
+}
+
+#[tokio::test]
+async fn test_pay_lnurl() {
+    let transport = MockTransport::new();
+    let zebedee_client = ZebedeeClient::new("apikey")
+        .try_domain("https://zbd.test")
+        .unwrap()
+        .transport(transport.clone());
+    let lnurl = encode_lnurl("https://service.example/lnurlp/alice").unwrap();
+    let destination: PaymentDestination = lnurl.parse().unwrap();
+    let params = r#"{
+        "tag": "payRequest",
+        "callback": "https://service.example/lnurlp/alice/cb?k=1",
+        "minSendable": 1000,
+        "maxSendable": 100000,
+        "metadata": "[[\"text/plain\",\"alice\"]]",
+        "commentAllowed": 10
+    }"#;
+
+    transport.respond(200, params);
+    transport.respond(200, format!(r#"{{"pr":"{INVOICE}","routes":[]}}"#));
+    transport.respond(
+        200,
+        r#"{"success":true,"data":{"id":"5d88b2e0-e491-40e1-a8a8-a81ae68f2297","unit":"msats","amount":"12000","description":"gg"}}"#,
+    );
+    let outcome = zebedee_client
+        .pay(&destination, "12000", "gg")
+        .await
+        .unwrap();
+    assert!(matches!(outcome, PaymentOutcome::Invoice(_)));
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].url, "https://service.example/lnurlp/alice");
+    assert_eq!(
+        requests[1].url,
+        "https://service.example/lnurlp/alice/cb?k=1&amount=12000&comment=gg"
+    );
+    assert_eq!(requests[0].header_value("apikey"), None);
+    assert_eq!(requests[1].header_value("apikey"), None);
+    assert_eq!(requests[2].url, "https://zbd.test/v0/payments");
+    let body: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+    assert_eq!(body["invoice"], INVOICE);
+
+    // an invoice for another amount is not paid
+    transport.respond(200, params);
+    transport.respond(200, format!(r#"{{"pr":"{INVOICE}"}}"#));
+    let err = zebedee_client
+        .pay(&destination, "13000", "")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("invoice amount"), "{err}");
+
+    transport.respond(200, params);
+    let err = zebedee_client
+        .pay(&destination, "500", "")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("below the minimum"), "{err}");
+
+    transport.respond(200, r#"{"status":"ERROR","reason":"unknown user"}"#);
+    let err = zebedee_client
+        .pay(&destination, "12000", "")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown user"), "{err}");
+    assert_eq!(transport.requests().len(), 7);
+}
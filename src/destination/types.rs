@@ -0,0 +1,132 @@
+use crate::{
+    email::EmailPaymentRes,
+    errors::ErrorMsg,
+    gamertag::GamertagPaymentData,
+    keysend::KeysendData,
+    ln_address::{LightningAddress, LnSendPaymentData},
+    payments::PaymentsData,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+const INVOICE_PREFIXES: [&str; 5] = ["lnbcrt", "lntbs", "lnbc", "lntb", "lnsb"];
+
+/// Anything a user may type to get paid, parsed with [`str::parse`].
+///
+/// A `lightning:` prefix is stripped first. `user@domain` is read as a Lightning Address,
+/// use a `mailto:` prefix to pay an email instead. `@name` is a ZBD Gamertag, a bare name is
+/// rejected so a mistyped invoice or key is never paid to a gamertag.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum PaymentDestination {
+    /// Bolt 11 invoice, the amount of the invoice is paid
+    Invoice(String),
+    /// Bech32 encoded LNURL
+    Lnurl(String),
+    LnAddress(String),
+    Gamertag(String),
+    Email(String),
+    /// Lightning Network node public key, paid with keysend
+    NodePubkey(String),
+}
+
+impl FromStr for PaymentDestination {
+    type Err = ErrorMsg;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim();
+        let input = strip_prefix_ignore_case(input, "lightning:").unwrap_or(input);
+        let input = input.trim_start_matches("//");
+        let lower = input.to_ascii_lowercase();
+        let bad = || ErrorMsg::BadDestination(s.to_owned());
+
+        if lower.starts_with("lnurl1") {
+            return Ok(PaymentDestination::Lnurl(lower));
+        }
+        if INVOICE_PREFIXES.iter().any(|p| lower.starts_with(p)) && lower.len() > 90 {
+            return match lower.chars().all(|c| c.is_ascii_alphanumeric()) {
+                true => Ok(PaymentDestination::Invoice(lower)),
+                false => Err(bad()),
+            };
+        }
+        if let Some(email) = strip_prefix_ignore_case(input, "mailto:") {
+            return match is_address(email) {
+                true => Ok(PaymentDestination::Email(email.to_lowercase())),
+                false => Err(bad()),
+            };
+        }
+        if let Some(gamertag) = input.strip_prefix('@') {
+            return match is_gamertag(gamertag) {
+                true => Ok(PaymentDestination::Gamertag(gamertag.to_owned())),
+                false => Err(bad()),
+            };
+        }
+        if input.contains('@') {
+            return match input.parse::<LightningAddress>() {
+                Ok(address) => Ok(PaymentDestination::LnAddress(address.to_string())),
+                Err(_) => Err(bad()),
+            };
+        }
+        if lower.len() == 66
+            && (lower.starts_with("02") || lower.starts_with("03"))
+            && lower.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Ok(PaymentDestination::NodePubkey(lower));
+        }
+        Err(bad())
+    }
+}
+
+impl Display for PaymentDestination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentDestination::Invoice(invoice) => f.write_str(invoice),
+            PaymentDestination::Lnurl(lnurl) => f.write_str(lnurl),
+            PaymentDestination::LnAddress(address) => f.write_str(address),
+            PaymentDestination::Gamertag(gamertag) => write!(f, "@{gamertag}"),
+            PaymentDestination::Email(email) => write!(f, "mailto:{email}"),
+            PaymentDestination::NodePubkey(pubkey) => f.write_str(pubkey),
+        }
+    }
+}
+
+/// Response data of a payment made with `ZebedeeClient::pay`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum PaymentOutcome {
+    Invoice(PaymentsData),
+    LnAddress(LnSendPaymentData),
+    Gamertag(GamertagPaymentData),
+    Email(EmailPaymentRes),
+    Keysend(KeysendData),
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    match s.get(..prefix.len()) {
+        Some(head) if head.eq_ignore_ascii_case(prefix) => Some(&s[prefix.len()..]),
+        _ => None,
+    }
+}
+
+fn is_address(s: &str) -> bool {
+    match s.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty()
+                && !user.contains(char::is_whitespace)
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && domain
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '.')
+        }
+        None => false,
+    }
+}
+
+fn is_gamertag(s: &str) -> bool {
+    !s.is_empty()
+        && s.len() <= 64
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
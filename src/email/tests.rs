@@ -5,17 +5,15 @@ use super::*;
 
 #[tokio::test]
 async fn test_pay_email() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
     let email = env::var("EMAIL").unwrap();
 
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let email_payment_req = EmailPaymentReqest {
         email,
         amount: "1000".to_owned(),
         comment: "from rust sdk test".to_owned(),
+        ..Default::default()
     };
 
     println!("email test: {:#?}", email_payment_req);
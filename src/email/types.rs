@@ -1,4 +1,8 @@
-use crate::{custom_deserializer::deserialize_from_string, StdResp, VoucherData};
+use crate::{
+    custom_deserializer::deserialize_from_string,
+    ids::{TransactionId, UserId},
+    StdResp, VoucherData,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -22,17 +26,17 @@ pub struct EmailPaymentData {
     pub amount: u64,
     pub comment: String,
     #[serde(rename = "receiverId")]
-    pub receiver_id: String,
+    pub receiver_id: UserId,
     #[serde(rename = "senderTxId")]
-    pub sender_tx_id: String,
+    pub sender_tx_id: TransactionId,
     #[serde(rename = "settledAt")]
     pub settled_at: DateTime<Utc>,
     #[serde(rename = "transactionId")]
-    pub transaction_id: String,
+    pub transaction_id: TransactionId,
 }
 
 /// Send instant Bitcoin payments to any email
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct EmailPaymentReqest {
     /// Recipient email to send payment to.
     pub email: String,
@@ -40,4 +44,11 @@ pub struct EmailPaymentReqest {
     pub amount: String,
     /// comment to be sent with the payment (max 150 characters).
     pub comment: String,
+    /// Your own reference for the payment, echoed back in transactions
+    #[serde(
+        rename = "internalId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub internal_id: Option<String>,
 }
@@ -1,3 +1,4 @@
+use crate::{api::Endpoint, lnurl::LnurlViolation, spend_policy::SpendViolation};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
@@ -5,14 +6,24 @@ use std::fmt::Display;
 #[derive(thiserror::Error, Debug)]
 pub enum ZebedeeError {
     /// Error from reqwest crate which is used to make HTTP requests
+    #[cfg(feature = "reqwest")]
     #[error("{0}")]
     InvalidRequest(#[from] reqwest::Error),
     /// Serde json Errors when parsing
     #[error("Unable to parse json: {0}")]
     InvalidJson(#[from] serde_json::Error),
+    /// Toml Errors when parsing a config file
+    #[error("Unable to parse toml: {0}")]
+    InvalidToml(#[from] toml::de::Error),
+    /// IO Errors when reading a config file
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
     /// Serde json Errors when parsing
     #[error("{0}")]
     Validate(#[from] validator::ValidationErrors),
+    /// Error from an HTTP transport other than reqwest, such as a tower service
+    #[error("{0}")]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
     /// Error messages from Zebedee REST API
     #[error("{0}")]
     Api(ApiError),
@@ -29,6 +40,16 @@ pub struct ApiError {
     pub message: Option<String>,
     /// Status of API call
     pub success: bool,
+    /// HTTP status code of the response, not part of the body
+    #[serde(skip)]
+    pub status: Option<u16>,
+}
+
+impl ApiError {
+    /// Whether the API answered 404 Not Found
+    pub fn is_not_found(&self) -> bool {
+        self.status == Some(404)
+    }
 }
 
 /// General Error messages
@@ -40,9 +61,80 @@ pub enum ErrorMsg {
     /// Bad payload data
     #[error("Bad payload data {0}")]
     BadPayloadData(String),
+    /// The API accepted a request but sent no data back, it may still have taken effect
+    #[error("No response data {0}")]
+    NoResponseData(String),
+    /// Not an absolute http(s) url
+    #[error("Bad url {0}")]
+    BadUrl(String),
+    /// PKCE verifier or seed rejected
+    #[error("Bad PKCE input {0}")]
+    BadPkce(String),
     /// Bad LN Address
     #[error("Bad LN Address {0}, ValidationError {1}")]
     BadLnAddress(String, String),
+    /// Required environment variable is not set
+    #[error("Missing environment variable {0}")]
+    MissingEnvVar(String),
+    /// Environment is neither a known preset nor a valid url
+    #[error("Bad environment {0}")]
+    BadEnvironment(String),
+    /// Profile not found in config file
+    #[error("Unknown profile {0}")]
+    UnknownProfile(String),
+    /// Project not registered in `ZebedeeProjects`
+    #[error("Unknown project {0}")]
+    UnknownProject(String),
+    /// Project has no wallet id to receive internal transfers
+    #[error("No wallet id set for project {0}")]
+    MissingWalletId(String),
+    /// Input is not a recognized payment destination
+    #[error("Bad payment destination {0}")]
+    BadDestination(String),
+    /// Recognized payment destination that cannot be paid through the API
+    #[error("Unsupported payment destination {0}")]
+    UnsupportedDestination(String),
+    /// Idempotency key is already used for a different kind of request
+    #[error("Idempotency key {0} reused for a different request")]
+    IdempotencyKeyReused(String),
+    /// Idempotency key is claimed by a request whose outcome is not known yet
+    #[error("Idempotency key {0} is in flight")]
+    IdempotencyInFlight(String),
+    /// Id is not a UUID, holds the id type and the rejected value
+    #[error("Bad {0} {1}")]
+    BadId(&'static str, String),
+    /// Not a valid bech32 LNURL or LNURL url
+    #[error("Bad LNURL {0}")]
+    BadLnurl(String),
+    /// Amount or comment rejected before reaching the LNURL service
+    #[error("LNURL limit exceeded: {0}")]
+    LnurlLimit(LnurlViolation),
+    /// Data does not fit in a QR code or the image could not be encoded
+    #[error("Unable to render QR code: {0}")]
+    QrCode(String),
+    /// No HTTP backend feature is enabled and no transport was set
+    #[error("No HTTP transport, enable the reqwest or ureq feature or set one with ZebedeeClient::transport")]
+    NoHttpTransport,
+    /// `FakeZebedeeApi` has no response programmed for the endpoint
+    #[error("No fake response for {0:?}")]
+    NoFakeResponse(Endpoint),
+    /// Payout rejected by the client's spend policy
+    #[error("Spend limit exceeded: {0}")]
+    LimitExceeded(SpendViolation),
+}
+
+impl ZebedeeError {
+    /// Whether the request certainly had no effect: rejected by local checks before it was
+    /// sent, or refused by the API with a 4xx status. A 5xx, a transport error or a response
+    /// without data may come after the API acted on the request.
+    pub(crate) fn is_definitely_not_sent(&self) -> bool {
+        match self {
+            ZebedeeError::Msg(ErrorMsg::NoResponseData(_)) => false,
+            ZebedeeError::Validate(_) | ZebedeeError::Msg(_) => true,
+            ZebedeeError::Api(e) => e.status.is_some_and(|status| (400..500).contains(&status)),
+            _ => false,
+        }
+    }
 }
 
 impl From<ErrorMsg> for ZebedeeError {
@@ -0,0 +1,97 @@
+use super::*;
+use crate::{
+    models::UnitType,
+    reconcile::RecordKind,
+    transaction::{Direction, Transaction},
+    utilities::BtcUsdData,
+};
+use chrono::{TimeZone, Utc};
+
+fn transaction(id: &str, settled_day: Option<u32>, amount: u64) -> Transaction {
+    Transaction {
+        id: id.to_owned(),
+        direction: Direction::Incoming,
+        amount: Some(amount),
+        fee: Some(1),
+        unit: UnitType::Sats,
+        status: String::from("completed"),
+        created_at: None,
+        settled_at: settled_day.map(|d| Utc.with_ymd_and_hms(2023, 5, d, 12, 0, 0).unwrap()),
+        counterparty: Some(String::from("memo, with \"quotes\"")),
+        internal_id: None,
+    }
+}
+
+#[tokio::test]
+async fn test_export_rows_range_and_usd() {
+    let transactions = vec![
+        (RecordKind::Charge, transaction("april", Some(1), 10)),
+        (RecordKind::Charge, transaction("may", Some(15), 1_000)),
+        (RecordKind::Payment, transaction("pending", None, 5)),
+    ];
+    let options = ExportOptions {
+        from: Some(Utc.with_ymd_and_hms(2023, 5, 10, 0, 0, 0).unwrap()),
+        to: Some(Utc.with_ymd_and_hms(2023, 6, 1, 0, 0, 0).unwrap()),
+        include_current_usd: true,
+        ..Default::default()
+    };
+    let price = BtcUsdData {
+        btc_usd_price: String::from("30000"),
+        btc_usd_timestamp: String::from("1684000000"),
+    };
+
+    let rows = export_rows(&transactions, &options, Some(&price)).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].id, "may");
+    assert_eq!(rows[0].amount_msats, Some(1_000_000));
+    assert_eq!(rows[0].amount_sats.as_deref(), Some("1000.000"));
+    assert_eq!(rows[0].fee_sats.as_deref(), Some("1.000"));
+    assert_eq!(rows[0].current_usd_value.as_deref(), Some("0.300000"));
+    assert_eq!(rows[0].current_btc_usd_price.as_deref(), Some("30000"));
+
+    let all = export_rows(&transactions, &ExportOptions::default(), None).unwrap();
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[0].current_usd_value, None);
+}
+
+#[test]
+fn test_usd_value_is_exact() {
+    let price = |s| parse_decimal(s).unwrap();
+    // 1 sat at 43250.17 USD/BTC is 0.0004325017 USD
+    assert_eq!(
+        usd_value(1_000, price("43250.17")).as_deref(),
+        Some("0.000433")
+    );
+    assert_eq!(
+        usd_value(2_100_000_000_000_000_000, price("100000")).as_deref(),
+        Some("2100000000000.000000")
+    );
+    assert_eq!(
+        usd_value(123_456_789, price(".5")).as_deref(),
+        Some("0.000617")
+    );
+    for bad in ["", ".", "1,000", "-1", "1e5", "12.3.4"] {
+        assert_eq!(parse_decimal(bad), None, "{bad}");
+    }
+}
+
+#[tokio::test]
+async fn test_write_export_formats() {
+    let transactions = vec![(RecordKind::Charge, transaction("may", Some(15), 2))];
+    let rows = export_rows(&transactions, &ExportOptions::default(), None).unwrap();
+
+    let mut csv = Vec::new();
+    write_export(&rows, ExportFormat::Csv, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("kind,id,direction,status,amount_msats"));
+    assert!(lines[1].starts_with("charge,may,incoming,completed,2000,2.000,1000,1.000,,"));
+    assert!(lines[1].contains("\"memo, with \"\"quotes\"\"\""));
+
+    let mut jsonl = Vec::new();
+    write_export(&rows, ExportFormat::JsonLines, &mut jsonl).unwrap();
+    let row: serde_json::Value = serde_json::from_slice(jsonl.trim_ascii_end()).unwrap();
+    assert_eq!(row["kind"], "charge");
+    assert_eq!(row["amount_msats"], 2000);
+}
@@ -0,0 +1,215 @@
+use crate::{
+    reconcile::RecordKind,
+    transaction::{Direction, Transaction},
+    utilities::BtcUsdData,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    JsonLines,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Only export transactions settled (or created, when not settled) at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only export transactions settled (or created, when not settled) before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Adds the current USD value of every row. The API only serves the latest BTC/USD price,
+    /// so rows are priced at the rate fetched when the export runs, not when they settled.
+    pub include_current_usd: bool,
+}
+
+/// A single row of a transaction history export
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportRow {
+    pub kind: RecordKind,
+    pub id: String,
+    pub direction: Direction,
+    pub status: String,
+    pub amount_msats: Option<u64>,
+    pub amount_sats: Option<String>,
+    pub fee_msats: Option<u64>,
+    pub fee_sats: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub settled_at: Option<DateTime<Utc>>,
+    pub internal_id: Option<String>,
+    pub counterparty: Option<String>,
+    /// BTC/USD rate when the export ran
+    pub current_btc_usd_price: Option<String>,
+    /// Amount valued at `current_btc_usd_price`, 6 decimals
+    pub current_usd_value: Option<String>,
+}
+
+const CSV_HEADER: [&str; 14] = [
+    "kind",
+    "id",
+    "direction",
+    "status",
+    "amount_msats",
+    "amount_sats",
+    "fee_msats",
+    "fee_sats",
+    "created_at",
+    "settled_at",
+    "internal_id",
+    "counterparty",
+    "current_btc_usd_price",
+    "current_usd_value",
+];
+
+/// Filters `transactions` to the requested date range and normalizes their amounts
+pub fn export_rows(
+    transactions: &[(RecordKind, Transaction)],
+    options: &ExportOptions,
+    price: Option<&BtcUsdData>,
+) -> crate::Result<Vec<ExportRow>> {
+    let price = price
+        .map(|p| {
+            parse_decimal(&p.btc_usd_price)
+                .map(|usd| (p.btc_usd_price.clone(), usd))
+                .ok_or_else(|| {
+                    crate::errors::ErrorMsg::BadPayloadData(format!(
+                        "btc usd price {}",
+                        p.btc_usd_price
+                    ))
+                })
+        })
+        .transpose()?;
+
+    let in_range = |tx: &Transaction| {
+        if options.from.is_none() && options.to.is_none() {
+            return true;
+        }
+        match tx.settled_at.or(tx.created_at) {
+            Some(at) => {
+                options.from.map(|from| at >= from).unwrap_or(true)
+                    && options.to.map(|to| at < to).unwrap_or(true)
+            }
+            None => false,
+        }
+    };
+
+    Ok(transactions
+        .iter()
+        .filter(|(_, tx)| in_range(tx))
+        .map(|(kind, tx)| {
+            let amount_msats = tx.amount_msats();
+            let fee_msats = tx.fee_msats();
+            ExportRow {
+                kind: *kind,
+                id: tx.id.clone(),
+                direction: tx.direction,
+                status: tx.status.clone(),
+                amount_msats,
+                amount_sats: amount_msats.map(format_sats),
+                fee_msats,
+                fee_sats: fee_msats.map(format_sats),
+                created_at: tx.created_at,
+                settled_at: tx.settled_at,
+                internal_id: tx.internal_id.clone(),
+                counterparty: tx.counterparty.clone(),
+                current_btc_usd_price: price.as_ref().map(|(raw, _)| raw.clone()),
+                current_usd_value: price
+                    .as_ref()
+                    .and_then(|(_, usd)| amount_msats.and_then(|msats| usd_value(msats, *usd))),
+            }
+        })
+        .collect())
+}
+
+pub fn write_export<W: Write>(
+    rows: &[ExportRow],
+    format: ExportFormat,
+    mut writer: W,
+) -> crate::Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            writeln!(writer, "{}", CSV_HEADER.join(","))?;
+            for row in rows {
+                let fields = [
+                    serde_json::to_value(row.kind)?
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_owned(),
+                    row.id.clone(),
+                    serde_json::to_value(row.direction)?
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_owned(),
+                    row.status.clone(),
+                    opt(&row.amount_msats),
+                    opt(&row.amount_sats),
+                    opt(&row.fee_msats),
+                    opt(&row.fee_sats),
+                    opt(&row.created_at.map(|at| at.to_rfc3339())),
+                    opt(&row.settled_at.map(|at| at.to_rfc3339())),
+                    opt(&row.internal_id),
+                    opt(&row.counterparty),
+                    opt(&row.current_btc_usd_price),
+                    opt(&row.current_usd_value),
+                ];
+                let line: Vec<_> = fields.iter().map(|f| csv_escape(f)).collect();
+                writeln!(writer, "{}", line.join(","))?;
+            }
+        }
+        ExportFormat::JsonLines => {
+            for row in rows {
+                serde_json::to_writer(&mut writer, row)?;
+                writeln!(writer)?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Decimal string such as `43250.17` as digits and the number of decimals
+pub(crate) fn parse_decimal(s: &str) -> Option<(u128, u32)> {
+    let (whole, fraction) = s.trim().split_once('.').unwrap_or((s.trim(), ""));
+    if whole.is_empty() && fraction.is_empty() || fraction.len() > 18 {
+        return None;
+    }
+    let digits = format!("{whole}{fraction}");
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((digits.parse().ok()?, fraction.len() as u32))
+}
+
+/// `msats` at `price` USD per BTC, rounded half up to 6 decimals
+pub(crate) fn usd_value(msats: u64, (price, decimals): (u128, u32)) -> Option<String> {
+    // 1 BTC is 10^11 msats, the result is kept in millionths of a dollar
+    let divisor = 10u128.checked_pow(11 + decimals)?;
+    let micro_usd = (msats as u128)
+        .checked_mul(price)?
+        .checked_mul(1_000_000)?
+        .checked_add(divisor / 2)?
+        / divisor;
+    Some(format!(
+        "{}.{:06}",
+        micro_usd / 1_000_000,
+        micro_usd % 1_000_000
+    ))
+}
+
+fn format_sats(msats: u64) -> String {
+    format!("{}.{:03}", msats / 1000, msats % 1000)
+}
+
+fn opt<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+fn csv_escape(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_owned(),
+    }
+}
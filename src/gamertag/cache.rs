@@ -0,0 +1,292 @@
+use crate::{api::ZebedeeApi, errors::ZebedeeError, ids::UserId, Instant, ZebedeeClient};
+use futures::{stream, StreamExt, TryStreamExt};
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    value: T,
+    /// `None` when the ttl is too long to represent
+    expires_at: Option<Instant>,
+}
+
+impl<T> Entry<T> {
+    fn is_fresh(&self) -> bool {
+        self.expires_at.is_none_or(|at| at > Instant::now())
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    /// Lower-cased gamertag to user id, `None` for unknown gamertags
+    user_ids: HashMap<String, Entry<Option<UserId>>>,
+    /// User id to current gamertag
+    gamertags: HashMap<UserId, Entry<String>>,
+}
+
+impl CacheState {
+    /// Called before adding an entry. Once `max_entries` is reached the expired entries are
+    /// dropped, then the ones closest to expiry until 90% of the cap is left, so the full
+    /// scan only runs every tenth of the cap.
+    fn make_room(&mut self, max_entries: usize) {
+        if self.user_ids.len() < max_entries {
+            return;
+        }
+        let now = Instant::now();
+        let fresh = |at: &Option<Instant>| at.is_none_or(|at| at > now);
+        self.user_ids.retain(|_, entry| fresh(&entry.expires_at));
+        self.gamertags.retain(|_, entry| fresh(&entry.expires_at));
+
+        let target = max_entries - max_entries.div_ceil(10);
+        if self.user_ids.len() <= target {
+            return;
+        }
+        let mut by_expiry: Vec<(Option<Instant>, String)> = self
+            .user_ids
+            .iter()
+            .map(|(gamertag, entry)| (entry.expires_at, gamertag.clone()))
+            .collect();
+        // entries that never expire go last
+        by_expiry.sort_by_key(|(at, _)| (at.is_none(), *at));
+        let excess = by_expiry.len() - target;
+        for (_, gamertag) in by_expiry.into_iter().take(excess) {
+            if let Some(Entry {
+                value: Some(user_id),
+                ..
+            }) = self.user_ids.remove(&gamertag)
+            {
+                if self
+                    .gamertags
+                    .get(&user_id)
+                    .is_some_and(|entry| entry.value.eq_ignore_ascii_case(&gamertag))
+                {
+                    self.gamertags.remove(&user_id);
+                }
+            }
+        }
+    }
+
+    /// Records that `user_id` goes by `gamertag` now, dropping the mapping of its previous gamertag
+    fn link(&mut self, user_id: &UserId, gamertag: &str, expires_at: Option<Instant>) {
+        if let Some(previous) = self.gamertags.get(user_id) {
+            if !previous.value.eq_ignore_ascii_case(gamertag) {
+                self.user_ids.remove(&previous.value.to_lowercase());
+            }
+        }
+        // a freed gamertag may have been picked up by another user
+        if let Some(Entry {
+            value: Some(previous_owner),
+            ..
+        }) = self.user_ids.get(&gamertag.to_lowercase())
+        {
+            if previous_owner != user_id {
+                self.gamertags.remove(previous_owner);
+            }
+        }
+        self.gamertags.insert(
+            user_id.clone(),
+            Entry {
+                value: gamertag.to_owned(),
+                expires_at,
+            },
+        );
+        self.user_ids.insert(
+            gamertag.to_lowercase(),
+            Entry {
+                value: Some(user_id.clone()),
+                expires_at,
+            },
+        );
+    }
+}
+
+/// Caches `get_userid_by_gamertag` and `get_gamertag_by_userid` lookups.
+///
+/// User ids never change but gamertags can be renamed, so a lookup that reveals a new
+/// gamertag for a known user id drops the mapping of the old one.
+pub struct GamertagCache<A = ZebedeeClient> {
+    client: A,
+    ttl: Duration,
+    negative_ttl: Duration,
+    concurrency: usize,
+    max_entries: usize,
+    state: Mutex<CacheState>,
+}
+
+impl<A: ZebedeeApi> GamertagCache<A> {
+    pub fn new(client: A) -> Self {
+        Self {
+            client,
+            ttl: Duration::from_secs(600),
+            negative_ttl: Duration::from_secs(60),
+            concurrency: 8,
+            max_entries: 10_000,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// How long a resolved gamertag is trusted, defaults to 10 minutes
+    pub fn ttl(self, ttl: Duration) -> Self {
+        Self { ttl, ..self }
+    }
+
+    /// How long an unknown gamertag is remembered, defaults to 1 minute
+    pub fn negative_ttl(self, negative_ttl: Duration) -> Self {
+        Self {
+            negative_ttl,
+            ..self
+        }
+    }
+
+    /// Maximum number of lookups in flight during bulk resolution, defaults to 8
+    pub fn concurrency(self, concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Maximum number of cached gamertags, defaults to 10 000. Expired entries are dropped
+    /// first when it is reached, then the ones closest to expiry.
+    pub fn max_entries(self, max_entries: usize) -> Self {
+        Self {
+            max_entries: max_entries.max(1),
+            ..self
+        }
+    }
+
+    /// Number of cached gamertags, unknown ones included
+    pub fn len(&self) -> usize {
+        self.state().user_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn cached_user_id(&self, gamertag: &str) -> Option<Option<UserId>> {
+        let state = self.state();
+        state
+            .user_ids
+            .get(&gamertag.to_lowercase())
+            .filter(|entry| entry.is_fresh())
+            .map(|entry| entry.value.clone())
+    }
+
+    /// User id of `gamertag`, `None` when no ZBD user has this gamertag
+    pub async fn user_id(&self, gamertag: &str) -> crate::Result<Option<UserId>> {
+        if let Some(user_id) = self.cached_user_id(gamertag) {
+            return Ok(user_id);
+        }
+
+        let user_id = match self.client.get_userid_by_gamertag(gamertag).await {
+            Ok(resp) => resp.data.map(|data| data.id),
+            // the API answers unknown gamertags with 404 Not Found
+            Err(ZebedeeError::Api(e)) if e.is_not_found() => None,
+            Err(e) => return Err(e),
+        };
+        match &user_id {
+            Some(user_id) => self.insert(user_id, gamertag),
+            None => self.insert_unknown(gamertag),
+        }
+        Ok(user_id)
+    }
+
+    /// Current gamertag of `user_id`, `None` when the user id is unknown
+    pub async fn gamertag(&self, user_id: &UserId) -> crate::Result<Option<String>> {
+        {
+            let state = self.state();
+            if let Some(entry) = state.gamertags.get(user_id) {
+                if entry.is_fresh() {
+                    return Ok(Some(entry.value.clone()));
+                }
+            }
+        }
+
+        let gamertag = match self.client.get_gamertag_by_userid(user_id).await {
+            Ok(resp) => resp.data.map(|data| data.gamertag),
+            Err(ZebedeeError::Api(e)) if e.is_not_found() => None,
+            Err(e) => return Err(e),
+        };
+        if let Some(gamertag) = &gamertag {
+            self.insert(user_id, gamertag);
+        }
+        Ok(gamertag)
+    }
+
+    /// Resolves many gamertags at once, keeping at most `concurrency` lookups in flight
+    pub async fn user_ids<I, T>(
+        &self,
+        gamertags: I,
+    ) -> crate::Result<HashMap<String, Option<UserId>>>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let gamertags: Vec<String> = gamertags
+            .into_iter()
+            .map(|gamertag| gamertag.as_ref().to_owned())
+            .collect();
+
+        stream::iter(gamertags)
+            .map(|gamertag| async move {
+                let user_id = self.user_id(&gamertag).await?;
+                crate::Result::<_>::Ok((gamertag, user_id))
+            })
+            .buffer_unordered(self.concurrency)
+            .try_collect()
+            .await
+    }
+
+    /// Records a known mapping, for example from a webhook or a payment response
+    pub fn insert(&self, user_id: &UserId, gamertag: &str) {
+        let expires_at = Instant::now().checked_add(self.ttl);
+        let mut state = self.state();
+        state.make_room(self.max_entries);
+        state.link(user_id, gamertag, expires_at);
+    }
+
+    pub(crate) fn insert_unknown(&self, gamertag: &str) {
+        let entry = Entry {
+            value: None,
+            expires_at: Instant::now().checked_add(self.negative_ttl),
+        };
+        let mut state = self.state();
+        state.make_room(self.max_entries);
+        state.user_ids.insert(gamertag.to_lowercase(), entry);
+    }
+
+    /// Records that `user_id` renamed to `gamertag`, forgetting the old gamertag
+    pub fn rename(&self, user_id: &UserId, gamertag: &str) {
+        self.insert(user_id, gamertag)
+    }
+
+    /// Forgets the mapping of `gamertag`, including a cached unknown result
+    pub fn invalidate_gamertag(&self, gamertag: &str) {
+        let mut state = self.state();
+        if let Some(Entry {
+            value: Some(user_id),
+            ..
+        }) = state.user_ids.remove(&gamertag.to_lowercase())
+        {
+            state.gamertags.remove(&user_id);
+        }
+    }
+
+    /// Forgets `user_id` and its gamertag
+    pub fn invalidate_user(&self, user_id: &UserId) {
+        let mut state = self.state();
+        if let Some(entry) = state.gamertags.remove(user_id) {
+            state.user_ids.remove(&entry.value.to_lowercase());
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.user_ids.clear();
+        state.gamertags.clear();
+    }
+}
@@ -1,4 +1,6 @@
+mod cache;
 mod types;
+pub use cache::*;
 pub use types::*;
 #[cfg(test)]
 mod tests;
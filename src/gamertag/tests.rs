@@ -1,13 +1,17 @@
 use super::*;
-use crate::ZebedeeClient;
-use std::env;
+use crate::{
+    api::{Endpoint, FakeZebedeeApi},
+    errors::ZebedeeError,
+    ids::{TransactionId, UserId},
+    transport::MockTransport,
+    ZebedeeClient,
+};
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
 
 #[tokio::test]
 async fn test_pay_gamertag() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let payment = GamertagPayment {
         gamertag: String::from("miketwenty1"),
@@ -21,10 +25,7 @@ async fn test_pay_gamertag() {
 
 #[tokio::test]
 async fn test_fetch_charge_from_gamertag() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let payment = GamertagPayment {
         gamertag: String::from("miketwenty1"),
@@ -43,12 +44,9 @@ async fn test_fetch_charge_from_gamertag() {
 
 #[tokio::test]
 async fn test_get_gamertag_tx() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
-    let transaction_id = String::from("322294d5-c993-4eef-88a8-8c9de099e16b");
+    let transaction_id: TransactionId = "322294d5-c993-4eef-88a8-8c9de099e16b".parse().unwrap();
 
     let r = zebedee_client
         .get_gamertag_tx(&transaction_id)
@@ -60,10 +58,7 @@ async fn test_get_gamertag_tx() {
 
 #[tokio::test]
 async fn test_get_userid_by_gamertag() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let gamertag = String::from("miketwenty1");
 
@@ -77,12 +72,9 @@ async fn test_get_userid_by_gamertag() {
 
 #[tokio::test]
 async fn test_get_gamertag_by_userid() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
-    let user_id = String::from("0a872b22-d3e2-46c8-84af-139cce32a4c5");
+    let user_id: UserId = "0a872b22-d3e2-46c8-84af-139cce32a4c5".parse().unwrap();
 
     let r = zebedee_client
         .get_gamertag_by_userid(&user_id)
@@ -91,3 +83,117 @@ async fn test_get_gamertag_by_userid() {
         .success;
     assert!(r);
 }
+
+#[tokio::test]
+async fn test_gamertag_cache() {
+    // no mock responses, every answer has to come from the cache
+    let zebedee_client = ZebedeeClient::new("apikey").transport(MockTransport::new());
+    let cache = GamertagCache::new(zebedee_client);
+
+    let user_id: UserId = "0a872b22-d3e2-46c8-84af-139cce32a4c5".parse().unwrap();
+    cache.insert(&user_id, "Satoshi");
+    cache.insert_unknown("nobody");
+    assert_eq!(
+        cache.user_id("satoshi").await.unwrap(),
+        Some(user_id.clone())
+    );
+    assert_eq!(
+        cache.gamertag(&user_id).await.unwrap(),
+        Some(String::from("Satoshi"))
+    );
+    assert_eq!(cache.user_id("nobody").await.unwrap(), None);
+
+    let resolved = cache.user_ids(["satoshi", "nobody"]).await.unwrap();
+    assert_eq!(resolved.len(), 2);
+    assert_eq!(resolved["nobody"], None);
+
+    // the old gamertag is no longer served after a rename
+    cache.rename(&user_id, "Nakamoto");
+    assert_eq!(
+        cache.gamertag(&user_id).await.unwrap(),
+        Some(String::from("Nakamoto"))
+    );
+    assert!(cache.user_id("satoshi").await.is_err());
+
+    cache.invalidate_user(&user_id);
+    assert!(cache.user_id("nakamoto").await.is_err());
+    assert!(cache.gamertag(&user_id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_gamertag_cache_only_remembers_not_found() {
+    let transport = MockTransport::new();
+    transport.respond(500, r#"{"success":false,"message":"Internal error"}"#);
+    transport.respond(404, r#"{"success":false,"message":"No user found"}"#);
+    let zebedee_client = ZebedeeClient::new("apikey").transport(transport.clone());
+    let cache = GamertagCache::new(zebedee_client).negative_ttl(Duration::MAX);
+
+    // an outage is not an unknown gamertag
+    let err = cache.user_id("nobody").await.unwrap_err();
+    assert!(matches!(err, ZebedeeError::Api(ref e) if e.status == Some(500)));
+    assert_eq!(cache.user_id("nobody").await.unwrap(), None);
+    assert_eq!(cache.user_id("nobody").await.unwrap(), None);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_gamertag_cache_is_bounded() {
+    let zebedee_client = ZebedeeClient::new("apikey").transport(MockTransport::new());
+    let cache = GamertagCache::new(zebedee_client)
+        .negative_ttl(Duration::ZERO)
+        .max_entries(10);
+    let user_id =
+        |i: usize| -> UserId { format!("0a872b22-d3e2-46c8-84af-{i:012}").parse().unwrap() };
+
+    for i in 0..5 {
+        cache.insert_unknown(&format!("gone{i}"));
+    }
+    for i in 0..8 {
+        cache.insert(&user_id(i), &format!("player{i}"));
+    }
+    // the expired unknown gamertags made room for the players
+    assert_eq!(cache.len(), 8);
+    assert!(cache.user_id("gone0").await.is_err());
+    for i in 0..8 {
+        assert_eq!(
+            cache.user_id(&format!("player{i}")).await.unwrap(),
+            Some(user_id(i))
+        );
+    }
+
+    for i in 8..100 {
+        cache.insert(&user_id(i), &format!("player{i}"));
+        assert!(cache.len() <= 10);
+    }
+    assert_eq!(cache.user_id("player99").await.unwrap(), Some(user_id(99)));
+    assert_eq!(
+        cache.gamertag(&user_id(99)).await.unwrap().as_deref(),
+        Some("player99")
+    );
+    assert!(cache.gamertag(&user_id(0)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_gamertag_cache_huge_ttl() {
+    let zebedee_client = ZebedeeClient::new("apikey").transport(MockTransport::new());
+    let cache = GamertagCache::new(zebedee_client).ttl(Duration::MAX);
+
+    let user_id: UserId = "0a872b22-d3e2-46c8-84af-139cce32a4c5".parse().unwrap();
+    cache.insert(&user_id, "Satoshi");
+    assert_eq!(cache.user_id("satoshi").await.unwrap(), Some(user_id));
+}
+
+#[tokio::test]
+async fn test_gamertag_cache_over_fake_api() {
+    let fake = Arc::new(FakeZebedeeApi::new());
+    fake.respond(
+        Endpoint::GetUseridByGamertag,
+        &json!({ "success": true, "data": { "id": "0a872b22-d3e2-46c8-84af-139cce32a4c5" } }),
+    )
+    .unwrap();
+    let cache = GamertagCache::new(fake.clone());
+
+    let user_id = cache.user_id("Satoshi").await.unwrap().unwrap();
+    assert_eq!(cache.user_id("satoshi").await.unwrap(), Some(user_id));
+    assert_eq!(fake.call_count(Endpoint::GetUseridByGamertag), 1);
+}
@@ -1,4 +1,7 @@
-use crate::StdResp;
+use crate::{
+    ids::{TransactionId, UserId},
+    StdResp,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -9,12 +12,12 @@ pub type GamertagTxResponse = StdResp<Option<GamertagTxData>>;
 pub type GamertagUserIdResponse = StdResp<Option<GamertagFromUserIdData>>;
 pub type IdFromGamertagResponse = StdResp<Option<IdFromUserGamertagData>>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GamertagPaymentData {
     #[serde(rename = "receiverId")]
-    pub receiver_id: String,
+    pub receiver_id: UserId,
     #[serde(rename = "transactionId")]
-    pub transaction_id: String,
+    pub transaction_id: TransactionId,
     pub amount: String,
     pub comment: String,
     #[serde(rename = "settledAt")]
@@ -31,6 +34,13 @@ pub struct GamertagPayment {
     #[validate(length(min = 4))]
     pub amount: String,
     pub description: String,
+    /// Your own reference for the payment, echoed back in transactions
+    #[serde(
+        rename = "internalId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub internal_id: Option<String>,
 }
 impl Default for GamertagPayment {
     fn default() -> Self {
@@ -38,6 +48,7 @@ impl Default for GamertagPayment {
             gamertag: String::from(""),
             amount: String::from(""),
             description: String::from("using zebedee rust sdk"),
+            internal_id: None,
         }
     }
 }
@@ -60,9 +71,9 @@ pub struct GamertagChargeData {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GamertagTxData {
-    pub id: String,
+    pub id: TransactionId,
     #[serde(rename = "receiverId")]
-    pub receiver_id: String,
+    pub receiver_id: UserId,
     pub amount: String,
     pub fee: String,
     pub unit: String,
@@ -76,7 +87,7 @@ pub struct GamertagTxData {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct IdFromUserGamertagData {
-    pub id: String,
+    pub id: UserId,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,158 @@
+use super::*;
+use crate::{
+    charges::Charge,
+    errors::{ErrorMsg, ZebedeeError},
+    payments::Payment,
+    reconcile::RecordKind,
+    spend_policy::SpendPolicy,
+    transport::{HttpMethod, MockTransport},
+    ZebedeeClient,
+};
+use chrono::Utc;
+
+fn offline_client() -> ZebedeeClient {
+    ZebedeeClient::new("apikey").transport(MockTransport::new())
+}
+
+#[tokio::test]
+async fn test_key_stays_pending_on_transport_error() {
+    let store = MemoryIdempotencyStore::default();
+    let charge = Charge {
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+
+    assert!(offline_client()
+        .send_idempotent("reward-1", &charge, &store)
+        .await
+        .is_err());
+
+    let record = store.get("reward-1").await.unwrap().unwrap();
+    assert_eq!(record.kind, RecordKind::Charge);
+    assert_eq!(record.state, IdempotencyState::Pending);
+}
+
+#[tokio::test]
+async fn test_key_released_when_rejected() {
+    let store = MemoryIdempotencyStore::default();
+    let zebedee_client = offline_client().spend_policy(SpendPolicy::new().max_per_transaction(1));
+    let payment = Payment {
+        invoice: String::from("lnbc120n1p0tdjwmpp5"),
+        ..Default::default()
+    };
+
+    let err = zebedee_client
+        .send_idempotent("reward-2", &payment, &store)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Spend limit exceeded"));
+    assert_eq!(store.get("reward-2").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_key_kept_unless_the_api_refused() {
+    let store = MemoryIdempotencyStore::default();
+    let transport = MockTransport::new();
+    transport.respond(400, r#"{"success":false,"message":"Invalid amount"}"#);
+    transport.respond(502, r#"{"success":false,"message":"Bad gateway"}"#);
+    let zebedee_client = ZebedeeClient::new("apikey").transport(transport);
+    let charge = Charge {
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+
+    assert!(zebedee_client
+        .send_idempotent("reward-6", &charge, &store)
+        .await
+        .is_err());
+    assert_eq!(store.get("reward-6").await.unwrap(), None);
+
+    assert!(zebedee_client
+        .send_idempotent("reward-6", &charge, &store)
+        .await
+        .is_err());
+    assert_eq!(
+        store.get("reward-6").await.unwrap().unwrap().state,
+        IdempotencyState::Pending
+    );
+}
+
+#[tokio::test]
+async fn test_key_reused_for_other_kind() {
+    let store = MemoryIdempotencyStore::default();
+    store
+        .put(IdempotencyRecord {
+            key: String::from("reward-3"),
+            kind: RecordKind::Payment,
+            state: IdempotencyState::Completed {
+                id: String::from("5d88b2e0-e491-40e1-a8a8-a81ae68f2297"),
+            },
+            created_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+
+    let err = offline_client()
+        .send_idempotent("reward-3", &Charge::default(), &store)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("reused"));
+
+    let payment = Payment::default().with_internal_id("reward-3");
+    assert_eq!(payment.internal_id, "reward-3");
+}
+
+fn pending(key: &str, kind: RecordKind) -> IdempotencyRecord {
+    IdempotencyRecord {
+        key: key.to_owned(),
+        kind,
+        state: IdempotencyState::Pending,
+        created_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_claim_is_insert_if_absent() {
+    let store = MemoryIdempotencyStore::default();
+    assert!(store
+        .try_claim(pending("reward-4", RecordKind::Charge))
+        .await
+        .unwrap());
+    assert!(!store
+        .try_claim(pending("reward-4", RecordKind::Payment))
+        .await
+        .unwrap());
+    assert_eq!(
+        store.get("reward-4").await.unwrap().unwrap().kind,
+        RecordKind::Charge
+    );
+}
+
+#[tokio::test]
+async fn test_pending_key_not_sent_again() {
+    let store = MemoryIdempotencyStore::default();
+    store
+        .put(pending("reward-5", RecordKind::Charge))
+        .await
+        .unwrap();
+    let transport = MockTransport::new();
+    transport.respond(200, r#"{"success":true,"data":[]}"#);
+    let zebedee_client = ZebedeeClient::new("apikey").transport(transport.clone());
+
+    let err = zebedee_client
+        .send_idempotent("reward-5", &Charge::default(), &store)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ZebedeeError::Msg(ErrorMsg::IdempotencyInFlight(ref key)) if key == "reward-5"
+    ));
+    // only the lookup, never a second charge
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, HttpMethod::Get);
+    assert_eq!(
+        store.get("reward-5").await.unwrap().unwrap().state,
+        IdempotencyState::Pending
+    );
+}
@@ -0,0 +1,198 @@
+use crate::{
+    api::ZebedeeApi, charges::*, payments::*, reconcile::RecordKind, withdrawal_request::*, StdResp,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Mutex};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum IdempotencyState {
+    /// Recorded before the request was sent, the outcome is unknown
+    Pending,
+    /// The API accepted the request and returned this id
+    Completed { id: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub key: String,
+    pub kind: RecordKind,
+    pub state: IdempotencyState,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Storage for idempotency keys, it must outlive the process to protect against crashes
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait IdempotencyStore: Send + Sync {
+    async fn get(&self, key: &str) -> crate::Result<Option<IdempotencyRecord>>;
+    /// Inserts `record` only if its key is absent, in one atomic step. Returns whether it was inserted.
+    async fn try_claim(&self, record: IdempotencyRecord) -> crate::Result<bool>;
+    async fn put(&self, record: IdempotencyRecord) -> crate::Result<()>;
+    async fn remove(&self, key: &str) -> crate::Result<()>;
+}
+
+/// In-process [`IdempotencyStore`], mostly useful for tests
+#[derive(Debug, Default)]
+pub struct MemoryIdempotencyStore {
+    records: Mutex<HashMap<String, IdempotencyRecord>>,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IdempotencyStore for MemoryIdempotencyStore {
+    async fn get(&self, key: &str) -> crate::Result<Option<IdempotencyRecord>> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(records.get(key).cloned())
+    }
+
+    async fn try_claim(&self, record: IdempotencyRecord) -> crate::Result<bool> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        if records.contains_key(&record.key) {
+            return Ok(false);
+        }
+        records.insert(record.key.clone(), record);
+        Ok(true)
+    }
+
+    async fn put(&self, record: IdempotencyRecord) -> crate::Result<()> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.insert(record.key.clone(), record);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> crate::Result<()> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.remove(key);
+        Ok(())
+    }
+}
+
+/// A request body that can be sent with `ZebedeeClient::send_idempotent`.
+/// The idempotency key is sent as the request's `internalId`.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait IdempotentRequest: Send + Sync {
+    type Data: Send;
+    const KIND: RecordKind;
+
+    fn with_internal_id(&self, internal_id: &str) -> Self;
+    fn id(data: &Self::Data) -> &str;
+    async fn send(&self, client: &dyn ZebedeeApi) -> crate::Result<StdResp<Option<Self::Data>>>;
+    async fn fetch(client: &dyn ZebedeeApi, id: &str)
+        -> crate::Result<StdResp<Option<Self::Data>>>;
+    /// Looks for an earlier request with this internal id in the list endpoint
+    async fn find(client: &dyn ZebedeeApi, internal_id: &str) -> crate::Result<Option<Self::Data>>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IdempotentRequest for Payment {
+    type Data = PaymentsData;
+    const KIND: RecordKind = RecordKind::Payment;
+
+    fn with_internal_id(&self, internal_id: &str) -> Self {
+        Payment {
+            internal_id: internal_id.to_owned(),
+            ..self.clone()
+        }
+    }
+
+    fn id(data: &PaymentsData) -> &str {
+        data.id.as_str()
+    }
+
+    async fn send(&self, client: &dyn ZebedeeApi) -> crate::Result<PaymentInvoiceResponse> {
+        client.pay_invoice(self).await
+    }
+
+    async fn fetch(client: &dyn ZebedeeApi, id: &str) -> crate::Result<FetchOnePaymentsResponse> {
+        client.get_payment(&id.parse()?).await
+    }
+
+    async fn find(
+        client: &dyn ZebedeeApi,
+        internal_id: &str,
+    ) -> crate::Result<Option<PaymentsData>> {
+        let payments = client.get_payments().await?.data.unwrap_or_default();
+        Ok(payments
+            .into_iter()
+            .find(|p| p.internal_id.as_deref() == Some(internal_id)))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IdempotentRequest for Charge {
+    type Data = ChargesData;
+    const KIND: RecordKind = RecordKind::Charge;
+
+    fn with_internal_id(&self, internal_id: &str) -> Self {
+        Charge {
+            internal_id: internal_id.to_owned(),
+            ..self.clone()
+        }
+    }
+
+    fn id(data: &ChargesData) -> &str {
+        data.id.as_str()
+    }
+
+    async fn send(&self, client: &dyn ZebedeeApi) -> crate::Result<FetchOneChargeResponse> {
+        client.create_charge(self).await
+    }
+
+    async fn fetch(client: &dyn ZebedeeApi, id: &str) -> crate::Result<FetchOneChargeResponse> {
+        client.get_charge(&id.parse()?).await
+    }
+
+    async fn find(
+        client: &dyn ZebedeeApi,
+        internal_id: &str,
+    ) -> crate::Result<Option<ChargesData>> {
+        let charges = client.get_charges().await?.data.unwrap_or_default();
+        Ok(charges.into_iter().find(|c| c.internal_id == internal_id))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IdempotentRequest for WithdrawalReqest {
+    type Data = WithdrawalRequestsData;
+    const KIND: RecordKind = RecordKind::Withdrawal;
+
+    fn with_internal_id(&self, internal_id: &str) -> Self {
+        WithdrawalReqest {
+            internal_id: internal_id.to_owned(),
+            ..self.clone()
+        }
+    }
+
+    fn id(data: &WithdrawalRequestsData) -> &str {
+        data.id.as_str()
+    }
+
+    async fn send(&self, client: &dyn ZebedeeApi) -> crate::Result<CreateWithdrawalResponse> {
+        client.create_withdrawal_request(self).await
+    }
+
+    async fn fetch(client: &dyn ZebedeeApi, id: &str) -> crate::Result<FetchOneWithdrawalResponse> {
+        client.get_withdrawal_request(&id.parse()?).await
+    }
+
+    async fn find(
+        client: &dyn ZebedeeApi,
+        internal_id: &str,
+    ) -> crate::Result<Option<WithdrawalRequestsData>> {
+        let withdrawals = client
+            .get_withdrawal_requests()
+            .await?
+            .data
+            .unwrap_or_default();
+        Ok(withdrawals
+            .into_iter()
+            .find(|w| w.internal_id == internal_id))
+    }
+}
@@ -0,0 +1,42 @@
+use super::*;
+use crate::charges::ChargesData;
+
+#[test]
+fn test_uuid_ids() {
+    let id: ChargeId = "5d88b2e0-e491-40e1-a8a8-a81ae68f2297".parse().unwrap();
+    assert_eq!(id, "5d88b2e0-e491-40e1-a8a8-a81ae68f2297");
+    assert_eq!(
+        PaymentId::new("5D88B2E0-E491-40E1-A8A8-A81AE68F2297")
+            .unwrap()
+            .as_str(),
+        "5D88B2E0-E491-40E1-A8A8-A81AE68F2297"
+    );
+
+    for bad in [
+        "",
+        "wallet-a",
+        "5d88b2e0e49140e1a8a8a81ae68f2297",
+        "5d88b2e0-e491-40e1-a8a8-a81ae68f229z",
+        "5d88b2e0-e491-40e1-a8a8-a81ae68f22970",
+    ] {
+        assert!(bad.parse::<WalletId>().is_err(), "{bad}");
+    }
+    let err = UserId::new("user-1").unwrap_err();
+    assert_eq!(err.to_string(), "Bad UserId user-1");
+}
+
+#[test]
+fn test_ids_serialize_transparently() {
+    let charge: ChargesData = serde_json::from_str(
+        r#"{"id":"5d88b2e0-e491-40e1-a8a8-a81ae68f2297","unit":"msats","amount":"1000",
+        "internalId":"","callbackUrl":"","description":"","status":"pending"}"#,
+    )
+    .unwrap();
+    assert_eq!(charge.id.as_str(), "5d88b2e0-e491-40e1-a8a8-a81ae68f2297");
+    assert_eq!(
+        serde_json::to_value(&charge.id).unwrap(),
+        serde_json::json!("5d88b2e0-e491-40e1-a8a8-a81ae68f2297")
+    );
+
+    assert!(serde_json::from_str::<ChargeId>(r#""not-a-uuid""#).is_err());
+}
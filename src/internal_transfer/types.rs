@@ -1,4 +1,7 @@
-use crate::StdResp;
+use crate::{
+    ids::{TransactionId, UserId, WalletId},
+    StdResp,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -10,15 +13,15 @@ pub struct InternalTransferData {
     pub status: String,
     pub amount: String,
     #[serde(rename = "senderWalletId")]
-    pub sender_wallet_id: String,
+    pub sender_wallet_id: WalletId,
     #[serde(rename = "receiverWalletId")]
-    pub receiver_wallet_id: String,
+    pub receiver_wallet_id: WalletId,
     #[serde(rename = "userId")]
-    pub user_id: String,
+    pub user_id: UserId,
     #[serde(rename = "sendTxId")]
-    pub send_tx_id: String,
+    pub send_tx_id: TransactionId,
     #[serde(rename = "receiveTxId")]
-    pub receive_tx_id: String,
+    pub receive_tx_id: TransactionId,
     #[serde(rename = "createdAt")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updatedAt")]
@@ -29,5 +32,5 @@ pub struct InternalTransferData {
 pub struct InternalTransfer {
     pub amount: String,
     #[serde(rename = "receiverWalletId")]
-    pub receiver_wallet_id: String,
+    pub receiver_wallet_id: WalletId,
 }
@@ -1,13 +1,9 @@
 use super::*;
 use crate::ZebedeeClient;
-use std::env;
 
 #[tokio::test]
 async fn test_keysend() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
     let tlvs = vec![TlvRecord {
         record_type: 123123123,
         value: String::from("00ABCDEF"),
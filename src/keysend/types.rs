@@ -1,14 +1,15 @@
+use crate::ids::{PaymentId, TransactionId, WalletId};
+use crate::StdResp;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use crate::StdResp;
 
 pub type KeysendResponse = StdResp<Option<KeysendData>>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeysendTx {
-    pub id: String,
+    pub id: TransactionId,
     #[serde(rename = "walletId")]
-    pub wallet_id: String,
+    pub wallet_id: WalletId,
     pub r#type: Option<String>,
     #[serde(rename = "totalAmount")]
     pub total_amount: String,
@@ -20,12 +21,12 @@ pub struct KeysendTx {
     pub confirmed_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeysendData {
     #[serde(rename = "keysendId")]
     pub keysend_id: String,
     #[serde(rename = "paymentId")]
-    pub payment_id: String,
+    pub payment_id: PaymentId,
     pub transaction: KeysendTx,
 }
 
@@ -40,6 +41,13 @@ pub struct Keysend {
     pub metadata: String,
     #[serde(rename = "callbackUrl")]
     pub callback_url: String,
+    /// Your own reference for the payment, echoed back in transactions
+    #[serde(
+        rename = "internalId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub internal_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,4 +55,4 @@ pub struct TlvRecord {
     #[serde(rename = "type")]
     pub record_type: u32,
     pub value: String, // Must be HEX-string encoded
-}
\ No newline at end of file
+}
@@ -1,14 +1,30 @@
+pub mod api;
+pub mod batch_payout;
 pub mod charges;
+pub mod config;
 mod custom_deserializer;
+pub mod destination;
 pub mod email;
 pub mod errors;
+pub mod export;
 pub mod gamertag;
+pub mod idempotency;
+pub mod ids;
 pub mod internal_transfer;
 pub mod keysend;
 pub mod ln_address;
+pub mod lnurl;
 pub mod login_with_zbd;
-mod models;
+pub mod models;
 pub mod payments;
+pub mod projects;
+#[cfg(feature = "qr")]
+pub mod qr;
+pub mod reconcile;
+pub mod secret;
+pub mod spend_policy;
+pub mod transaction;
+pub mod transport;
 pub mod utilities;
 pub mod voucher;
 pub mod wallet;
@@ -16,20 +32,32 @@ pub mod withdrawal_request;
 
 use std::borrow::Cow;
 
+use batch_payout::*;
 use charges::*;
+use chrono::Utc;
+use config::*;
+use destination::*;
 use email::*;
 use errors::*;
+use export::*;
 use gamertag::*;
+use idempotency::*;
+use ids::*;
 use internal_transfer::*;
 use keysend::*;
 use ln_address::*;
+use lnurl::*;
 use login_with_zbd::*;
+pub use login_with_zbd::{PkceMethod, PKCE};
 use payments::*;
-use rand::Rng;
-use reqwest::{RequestBuilder, Response};
+use reconcile::*;
+use secret::*;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
-use sha2::{Digest, Sha256};
+use spend_policy::*;
+use std::sync::Arc;
+use transaction::*;
+use transport::*;
 use utilities::*;
 use validator::Validate;
 use voucher::*;
@@ -38,31 +66,132 @@ use withdrawal_request::*;
 
 pub type Result<T, E = errors::ZebedeeError> = std::result::Result<T, E>;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+// std::time::Instant panics on wasm32-unknown-unknown
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
 #[derive(Clone, Debug)]
 pub struct ZebedeeClient {
     domain: String,
-    reqw_cli: reqwest::Client,
-    apikey: String,
+    transport: Arc<dyn HttpTransport>,
+    apikey: Secret,
     oauth: ZebedeeOauth,
+    spend_policy: Option<Arc<SpendPolicy>>,
+    ln_preflight: Option<Arc<LnAddressPreflight>>,
 }
 
 impl ZebedeeClient {
     pub fn new<'a>(apikey: impl Into<Cow<'a, str>>) -> Self {
         Self {
-            apikey: apikey.into().into_owned(),
-            domain: "https://api.zebedee.io".to_owned(),
-            reqw_cli: reqwest::Client::new(),
+            apikey: apikey.into().into(),
+            domain: Environment::Production.url().to_owned(),
+            transport: default_transport(),
             oauth: Default::default(),
+            spend_policy: None,
+            ln_preflight: None,
+        }
+    }
+
+    /// Builds a client from `ZBD_API_KEY`, `ZBD_ENV` and, when `ZBD_OAUTH_CLIENT_ID` is set,
+    /// `ZBD_OAUTH_SECRET`, `ZBD_REDIRECT_URI`, `ZBD_OAUTH_STATE` and `ZBD_OAUTH_SCOPE`.
+    pub fn from_env() -> Result<Self> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    pub(crate) fn from_lookup<F>(lookup: F) -> Result<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let required =
+            |name: &str| lookup(name).ok_or_else(|| ErrorMsg::MissingEnvVar(name.to_owned()));
+
+        let apikey = required("ZBD_API_KEY")?;
+        let environment = match lookup("ZBD_ENV") {
+            Some(env) => env.parse()?,
+            None => Environment::default(),
+        };
+        let client = Self::new(apikey).environment(environment);
+
+        match lookup("ZBD_OAUTH_CLIENT_ID") {
+            Some(client_id) => Ok(client.oauth(
+                client_id,
+                required("ZBD_OAUTH_SECRET")?,
+                required("ZBD_REDIRECT_URI")?,
+                lookup("ZBD_OAUTH_STATE").unwrap_or_default(),
+                lookup("ZBD_OAUTH_SCOPE").unwrap_or_else(|| "user".to_owned()),
+            )),
+            None => Ok(client),
         }
     }
 
-    /// Zebedee REST API url
+    /// Builds a client from a [`Profile`] loaded out of a [`ZebedeeConfig`] file
+    pub fn from_profile(profile: &Profile) -> Self {
+        let client =
+            Self::new(profile.apikey.expose_secret()).environment(profile.environment.clone());
+        match &profile.oauth {
+            Some(oauth) => Self {
+                oauth: oauth.clone(),
+                ..client
+            },
+            None => client,
+        }
+    }
+
+    /// Zebedee REST API url, taken as is. A malformed url only fails once a request is sent.
+    #[deprecated(note = "use `try_domain` or `environment`, which check the url")]
     pub fn domain(self, domain: String) -> Self {
         Self { domain, ..self }
     }
 
+    /// Like `domain`, but rejects anything that is not an absolute http(s) url
+    pub fn try_domain(self, domain: impl Into<String>) -> Result<Self> {
+        let domain = domain.into();
+        let url =
+            url::Url::parse(&domain).map_err(|e| ErrorMsg::BadUrl(format!("{domain}: {e}")))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+            return Err(ErrorMsg::BadUrl(domain).into());
+        }
+        Ok(Self { domain, ..self })
+    }
+
+    /// Zebedee REST API environment preset
+    pub fn environment(self, environment: Environment) -> Self {
+        Self {
+            domain: environment.url().to_owned(),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "reqwest")]
     pub fn reqw_cli(self, reqw_cli: reqwest::Client) -> Self {
-        Self { reqw_cli, ..self }
+        self.transport(ReqwestTransport::new(reqw_cli))
+    }
+
+    /// HTTP stack used for every request, see [`HttpTransport`]
+    pub fn transport<T: HttpTransport + 'static>(self, transport: T) -> Self {
+        self.shared_transport(Arc::new(transport))
+    }
+
+    /// Sends every request through a tower service, so middleware such as timeouts,
+    /// concurrency limits, retries or tracing can wrap the calls to the Zebedee REST API.
+    /// [`ReqwestService`](transport::ReqwestService) is the default HTTP stack to layer over.
+    #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+    pub fn transport_service<S>(self, service: S) -> Self
+    where
+        S: tower_service::Service<http::Request<Vec<u8>>, Response = http::Response<Vec<u8>>>
+            + Clone
+            + Send
+            + 'static,
+        S::Error: Into<transport::BoxError>,
+        S::Future: Send,
+    {
+        self.transport(TowerTransport::new(service))
+    }
+
+    pub(crate) fn shared_transport(self, transport: Arc<dyn HttpTransport>) -> Self {
+        Self { transport, ..self }
     }
     pub fn oauth(
         self,
@@ -76,13 +205,71 @@ impl ZebedeeClient {
         Self { oauth, ..self }
     }
 
-    async fn parse_response<T>(&self, resp: Response) -> Result<T>
+    /// Like `oauth`, but validates the client id, secret, state and redirect uri first
+    pub fn try_oauth(
+        self,
+        client_id: String,
+        secret: String,
+        redirect_uri: String,
+        state: String,
+        scope: String,
+    ) -> Result<Self> {
+        let oauth = ZebedeeOauth::new(client_id, secret, redirect_uri, state, scope);
+        oauth.validate()?;
+        Ok(Self { oauth, ..self })
+    }
+
+    /// Payout limits checked before `pay_gamertag`, `pay_ln_address`, `pay_email`, `keysend`
+    /// and `pay_invoice` requests are sent
+    pub fn spend_policy(self, spend_policy: SpendPolicy) -> Self {
+        Self {
+            spend_policy: Some(Arc::new(spend_policy)),
+            ..self
+        }
+    }
+
+    /// Checks `pay_ln_address` and `fetch_charge_ln_address` requests against the amount and
+    /// comment limits of the receiving Lightning Address before they are sent
+    pub fn ln_address_preflight(self, preflight: LnAddressPreflight) -> Self {
+        Self {
+            ln_preflight: Some(Arc::new(preflight)),
+            ..self
+        }
+    }
+
+    async fn spend_guard<T, F, A>(
+        &self,
+        destination: PaymentDestination,
+        amount: A,
+        payout: F,
+    ) -> Result<T>
+    where
+        F: std::future::Future<Output = Result<T>>,
+        A: FnOnce() -> Option<u64>,
+    {
+        match &self.spend_policy {
+            Some(policy) => {
+                let recipient = recipient_key(&destination);
+                let amount = amount().ok_or_else(|| {
+                    ErrorMsg::BadPayloadData(format!("unknown amount paying {recipient}"))
+                })?;
+                policy.guard(&recipient, amount, payout).await
+            }
+            None => payout.await,
+        }
+    }
+
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        self.transport.send(request).await
+    }
+
+    fn parse_response<T>(&self, resp: HttpResponse) -> Result<T>
     where
         T: DeserializeOwned,
     {
-        let is_success = resp.status().is_success();
+        let is_success = resp.is_success();
         // parse the resp body
-        let body = resp.json::<Value>().await?;
+        let body = serde_json::from_slice::<Value>(&resp.body)?;
 
         // based on success or error choose the appropriate data structure to deserialize
         match is_success {
@@ -91,36 +278,47 @@ impl ZebedeeClient {
                 Ok(body)
             }
             false => {
-                let err_body: ApiError = serde_json::from_value(body)?;
+                let err_body = ApiError {
+                    status: Some(resp.status),
+                    ..serde_json::from_value(body)?
+                };
                 Err(err_body.into())
             }
         }
     }
 
-    fn add_headers(&self, request_builder: RequestBuilder) -> RequestBuilder {
-        request_builder
+    fn add_headers(&self, request: HttpRequest) -> HttpRequest {
+        request
             .header("Content-Type", "application/json")
-            .header("apikey", &self.apikey)
+            .header("apikey", self.apikey.expose_secret())
     }
 
     /// Retrieves the total balance of a given Project Wallet.
     pub async fn get_wallet_details(&self) -> Result<WalletInfoResponse> {
         let url = format!("{}/v0/wallet", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Make payment directly to a Lightning Network node Public Key, without the need for a Payment Request / Charge.
     pub async fn keysend(&self, keysend_payload: &Keysend) -> Result<KeysendResponse> {
         let url = format!("{}/v0/keysend-payment", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(keysend_payload)
-            .send()
-            .await?;
+        let payout = async {
+            let request = self
+                .add_headers(HttpRequest::post(&url))
+                .json(keysend_payload)?;
+            let resp = self.send(request).await?;
+
+            self.parse_response(resp)
+        };
 
-        self.parse_response(resp).await
+        self.spend_guard(
+            PaymentDestination::NodePubkey(keysend_payload.pubkey.clone()),
+            || keysend_payload.amount.parse().ok(),
+            payout,
+        )
+        .await
     }
 
     /// Creates a new Charge / Payment Request in the Bitcoin Lightning Network, payable by any Lightning Network wallet.
@@ -129,29 +327,23 @@ impl ZebedeeClient {
     pub async fn create_charge(&self, charge: &Charge) -> Result<FetchOneChargeResponse> {
         let url = format!("{}/v0/charges", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(&charge)
-            .send()
-            .await?;
+        let request = self.add_headers(HttpRequest::post(&url)).json(&charge)?;
+        let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 
     pub async fn get_charges(&self) -> Result<FetchChargesResponse> {
         let url = format!("{}/v0/charges", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Retrieves all information relating a specific Charge / Payment Request.
-    pub async fn get_charge<T>(&self, charge_id: T) -> Result<FetchOneChargeResponse>
-    where
-        T: AsRef<str>,
-    {
-        let url = format!("{}/v0/charges/{}", &self.domain, charge_id.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+    pub async fn get_charge(&self, charge_id: &ChargeId) -> Result<FetchOneChargeResponse> {
+        let url = format!("{}/v0/charges/{}", &self.domain, charge_id);
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Send Bitcoin payments directly to a user's ZBD Gamertag
@@ -162,13 +354,19 @@ impl ZebedeeClient {
 
         let url = format!("{}/v0/gamertag/send-payment", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(payment)
-            .send()
-            .await?;
+        let payout = async {
+            let request = self.add_headers(HttpRequest::post(&url)).json(payment)?;
+            let resp = self.send(request).await?;
+
+            self.parse_response(resp)
+        };
 
-        self.parse_response(resp).await
+        self.spend_guard(
+            PaymentDestination::Gamertag(payment.gamertag.clone()),
+            || payment.amount.parse().ok(),
+            payout,
+        )
+        .await
     }
 
     /// Create a bolt 11 invoice so you can pay a specified gamertag
@@ -182,29 +380,25 @@ impl ZebedeeClient {
 
         let url = format!("{}/v0/gamertag/charges", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(payment)
-            .send()
-            .await?;
+        let request = self.add_headers(HttpRequest::post(&url)).json(payment)?;
+        let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 
     /// Get data on payments sent to ZBD Gamertags.
     /// The data payload returned will inform you of the status of that transaction as well as any associated fees.
-    pub async fn get_gamertag_tx<T>(&self, transaction_id: T) -> Result<GamertagTxResponse>
-    where
-        T: AsRef<str>,
-    {
+    pub async fn get_gamertag_tx(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<GamertagTxResponse> {
         let url = format!(
             "{}/v0/gamertag/transaction/{}",
-            &self.domain,
-            transaction_id.as_ref()
+            &self.domain, transaction_id
         );
 
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Get a given User's ID when provided with a ZBD Gamertag.
@@ -213,18 +407,38 @@ impl ZebedeeClient {
         T: AsRef<str>,
     {
         let url = format!("{}/v0/user-id/gamertag/{}", &self.domain, gamertag.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Get a given user's ZBD Gamertag from user id
-    pub async fn get_gamertag_by_userid<T>(&self, user_id: T) -> Result<GamertagUserIdResponse>
+    pub async fn get_gamertag_by_userid(&self, user_id: &UserId) -> Result<GamertagUserIdResponse> {
+        let url = format!("{}/v0/gamertag/user-id/{}", &self.domain, user_id);
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
+    }
+
+    /// Pays every recipient of the batch with bounded concurrency.
+    /// Individual failures are recorded in the report and do not stop the batch.
+    pub async fn batch_payout(&self, batch: &BatchPayout) -> PayoutReport {
+        self.resume_batch_payout(batch, PayoutReport::default(), |_| {})
+            .await
+    }
+
+    /// Runs a batch again, skipping the recipients in `report` that were paid or whose
+    /// outcome is unknown. Payouts left pending are looked up in `get_payments` instead.
+    /// `on_result` is called before each payout is sent and again as it completes, so the
+    /// report can be persisted.
+    pub async fn resume_batch_payout<F>(
+        &self,
+        batch: &BatchPayout,
+        report: PayoutReport,
+        on_result: F,
+    ) -> PayoutReport
     where
-        T: AsRef<str>,
+        F: FnMut(&PayoutResult),
     {
-        let url = format!("{}/v0/gamertag/user-id/{}", &self.domain, user_id.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        batch.run(self, report, on_result).await
     }
 
     /// Initiates a transfer of funds between two Project Wallets you own.
@@ -233,25 +447,36 @@ impl ZebedeeClient {
         internal_transfer_payload: &InternalTransfer,
     ) -> Result<InternalTransferResponse> {
         let url = format!("{}/v0/internal-transfer", &self.domain);
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(internal_transfer_payload)
-            .send()
-            .await?;
+        let request = self
+            .add_headers(HttpRequest::post(&url))
+            .json(internal_transfer_payload)?;
+        let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 
     /// Send Bitcoin payments directly to a Lightning Address.
     pub async fn pay_ln_address(&self, payment: &LnPayment) -> Result<PayLnAddressResponse> {
+        if let Some(preflight) = &self.ln_preflight {
+            preflight
+                .check(self, &payment.ln_address, &payment.amount, &payment.comment)
+                .await?;
+        }
+
         let url = format!("{}/v0/ln-address/send-payment", &self.domain);
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(payment)
-            .send()
-            .await?;
+        let payout = async {
+            let request = self.add_headers(HttpRequest::post(&url)).json(payment)?;
+            let resp = self.send(request).await?;
+
+            self.parse_response(resp)
+        };
 
-        self.parse_response(resp).await
+        self.spend_guard(
+            PaymentDestination::LnAddress(payment.ln_address.to_string()),
+            || payment.amount.parse().ok(),
+            payout,
+        )
+        .await
     }
 
     /// Create a Charge / Payment Request QR code for a Lightning Address
@@ -259,15 +484,18 @@ impl ZebedeeClient {
         &self,
         payment: &LnFetchCharge,
     ) -> Result<FetchLnChargeResponse> {
+        if let Some(preflight) = &self.ln_preflight {
+            preflight
+                .check(self, &payment.ln_address, &payment.amount, "")
+                .await?;
+        }
+
         let url = format!("{}/v0/ln-address/fetch-charge", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(payment)
-            .send()
-            .await?;
+        let request = self.add_headers(HttpRequest::post(&url)).json(payment)?;
+        let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 
     /// Validate whether a user's entered Lightning Address is indeed a real Lightning Address
@@ -284,38 +512,41 @@ impl ZebedeeClient {
             &self.domain, &lightning_address.address
         );
 
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 
     /// Pays a Charge / Payment Request in the Bitcoin Lightning Network
     pub async fn pay_invoice(&self, payment: &Payment) -> Result<PaymentInvoiceResponse> {
         let url = format!("{}/v0/payments", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(&payment)
-            .send()
-            .await?;
+        let payout = async {
+            let request = self.add_headers(HttpRequest::post(&url)).json(&payment)?;
+            let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+            self.parse_response(resp)
+        };
+
+        self.spend_guard(
+            PaymentDestination::Invoice(payment.invoice.clone()),
+            || payment.amount_msats(),
+            payout,
+        )
+        .await
     }
 
     pub async fn get_payments(&self) -> Result<FetchPaymentsResponse> {
         let url = format!("{}/v0/payments", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Retrieves all the information related to a specific Payment
-    pub async fn get_payment<T>(&self, payment_id: T) -> Result<FetchOnePaymentsResponse>
-    where
-        T: AsRef<str>,
-    {
-        let url = format!("{}/v0/payments/{}", &self.domain, payment_id.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+    pub async fn get_payment(&self, payment_id: &PaymentId) -> Result<FetchOnePaymentsResponse> {
+        let url = format!("{}/v0/payments/{}", &self.domain, payment_id);
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Check if provided ip address will be [supported](https://zebedee.io/countries) by Zebedee REST API
@@ -324,15 +555,15 @@ impl ZebedeeClient {
         T: AsRef<str>,
     {
         let url = format!("{}/v0/is-supported-region/{}", &self.domain, ip.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Check if callback response is from legit Zebedee ip address
     pub async fn get_prod_ips(&self) -> Result<ProdIpsResponse> {
         let url = format!("{}/v0/prod-ips", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Get the latest price for Bitcoin in US Dollars.
@@ -340,8 +571,8 @@ impl ZebedeeClient {
     /// partner exchange providers's price feeds.
     pub async fn get_btc_usd(&self) -> Result<BtcToUsdResponse> {
         let url = format!("{}/v0/btcusd", &self.domain);
-        let resp = self.reqw_cli.get(&url).send().await?;
-        self.parse_response(resp).await
+        let resp = self.send(HttpRequest::get(&url)).await?;
+        self.parse_response(resp)
     }
 
     /// Withdrawal Requests can be thought of as exact opposites to Charges.
@@ -357,36 +588,178 @@ impl ZebedeeClient {
     ) -> Result<CreateWithdrawalResponse> {
         let url = format!("{}/v0/withdrawal-requests", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(&withdrawal_request)
-            .send()
-            .await?;
+        let request = self
+            .add_headers(HttpRequest::post(&url))
+            .json(&withdrawal_request)?;
+        let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 
     pub async fn get_withdrawal_requests(&self) -> Result<FetchWithdrawalsResponse> {
         let url = format!("{}/v0/withdrawal-requests", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
     }
 
     /// Retrieves details about a specific Withdrawal Request.
-    pub async fn get_withdrawal_request<T>(
+    pub async fn get_withdrawal_request(
+        &self,
+        withdrawal_id: &WithdrawalId,
+    ) -> Result<FetchOneWithdrawalResponse> {
+        let url = format!("{}/v0/withdrawal-requests/{}", &self.domain, withdrawal_id);
+        let resp = self.send(self.add_headers(HttpRequest::get(&url))).await?;
+        self.parse_response(resp)
+    }
+
+    /// Sends a payment, charge or withdrawal request at most once per idempotency `key`.
+    /// The key is claimed in `store` before sending and used as the request's internal id.
+    /// Replaying a key looks up the earlier result instead of sending the request again.
+    /// The key is released only when the request is refused by local checks or a 4xx answer.
+    /// A key still pending with no matching request in the API is `IdempotencyInFlight`,
+    /// remove it from the store once the earlier attempt is known to have failed.
+    pub async fn send_idempotent<R>(
         &self,
-        withdrawal_id: T,
-    ) -> Result<FetchOneWithdrawalResponse>
+        key: &str,
+        request: &R,
+        store: &dyn IdempotencyStore,
+    ) -> Result<StdResp<Option<R::Data>>>
     where
-        T: AsRef<str>,
+        R: IdempotentRequest,
     {
-        let url = format!(
-            "{}/v0/withdrawal-requests/{}",
-            &self.domain,
-            withdrawal_id.as_ref()
-        );
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
-        self.parse_response(resp).await
+        let claim = IdempotencyRecord {
+            key: key.to_owned(),
+            kind: R::KIND,
+            state: IdempotencyState::Pending,
+            created_at: Utc::now(),
+        };
+        if !store.try_claim(claim).await? {
+            match store.get(key).await? {
+                Some(record) if record.kind != R::KIND => {
+                    return Err(ErrorMsg::IdempotencyKeyReused(key.to_owned()).into());
+                }
+                Some(IdempotencyRecord {
+                    state: IdempotencyState::Completed { id },
+                    ..
+                }) => return R::fetch(self, &id).await,
+                Some(record) => {
+                    // pending after a crash or while another task sends, look for the request
+                    let Some(data) = R::find(self, key).await? else {
+                        return Err(ErrorMsg::IdempotencyInFlight(key.to_owned()).into());
+                    };
+                    let id = R::id(&data).to_owned();
+                    store
+                        .put(IdempotencyRecord {
+                            state: IdempotencyState::Completed { id },
+                            ..record
+                        })
+                        .await?;
+                    return Ok(StdResp {
+                        success: true,
+                        data: Some(data),
+                        message: None,
+                    });
+                }
+                // released by a rejected attempt between the claim and the lookup
+                None => return Err(ErrorMsg::IdempotencyInFlight(key.to_owned()).into()),
+            }
+        }
+
+        match request.with_internal_id(key).send(self).await {
+            Ok(resp) => {
+                if let Some(data) = &resp.data {
+                    let id = R::id(data).to_owned();
+                    store
+                        .put(IdempotencyRecord {
+                            key: key.to_owned(),
+                            kind: R::KIND,
+                            state: IdempotencyState::Completed { id },
+                            created_at: Utc::now(),
+                        })
+                        .await?;
+                }
+                Ok(resp)
+            }
+            // refused requests never went through and are safe to send again, any other
+            // failure keeps the key pending until a retry finds the request by its key
+            Err(e) if e.is_definitely_not_sent() => {
+                store.remove(key).await?;
+                Err(e)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Every charge, payment and withdrawal request of the Project Wallet as [`Transaction`]s
+    pub async fn list_transactions(&self) -> Result<Vec<(RecordKind, Transaction)>> {
+        let (charges, payments, withdrawals) = futures::try_join!(
+            self.get_charges(),
+            self.get_payments(),
+            self.get_withdrawal_requests()
+        )?;
+
+        let charges = charges.data.unwrap_or_default();
+        let payments = payments.data.unwrap_or_default();
+        let withdrawals = withdrawals.data.unwrap_or_default();
+
+        Ok(charges
+            .iter()
+            .map(|c| (RecordKind::Charge, c.into()))
+            .chain(payments.iter().map(|p| (RecordKind::Payment, p.into())))
+            .chain(
+                withdrawals
+                    .iter()
+                    .map(|w| (RecordKind::Withdrawal, w.into())),
+            )
+            .collect())
+    }
+
+    /// Writes the transaction history between `options.from` and `options.to` as CSV or JSON Lines,
+    /// returns the number of rows
+    pub async fn export_transactions<W>(&self, options: &ExportOptions, writer: W) -> Result<usize>
+    where
+        W: std::io::Write,
+    {
+        let transactions = self.list_transactions().await?;
+
+        let price = match options.include_current_usd {
+            true => Some(
+                self.get_btc_usd()
+                    .await?
+                    .data
+                    .ok_or_else(|| ErrorMsg::BadPayloadData("no btc usd price".to_owned()))?,
+            ),
+            false => None,
+        };
+
+        let rows = export_rows(&transactions, options, price.as_ref())?;
+        write_export(&rows, options.format, writer)?;
+        Ok(rows.len())
+    }
+
+    /// Compares our own records with `get_charges`, `get_payments` and `get_withdrawal_requests`.
+    /// When `expected_balance` (in millisatoshis) is given the wallet balance is checked as well.
+    pub async fn reconcile(
+        &self,
+        expected: &[ExpectedRecord],
+        expected_balance: Option<u64>,
+    ) -> Result<ReconciliationReport> {
+        let actual = self.list_transactions().await?;
+        let mut report = reconcile_records(expected, &actual);
+
+        if let Some(expected_msats) = expected_balance {
+            let wallet = self
+                .get_wallet_details()
+                .await?
+                .data
+                .ok_or_else(|| ErrorMsg::BadPayloadData("no wallet data".to_owned()))?;
+            report.balance = Some(BalanceCheck {
+                expected_msats,
+                actual_msats: wallet.balance_msats()?,
+            });
+        }
+
+        Ok(report)
     }
 
     /// Send instant Bitcoin payments to any email.
@@ -396,37 +769,191 @@ impl ZebedeeClient {
     ) -> Result<EmailPaymentResponse> {
         let url = format!("{}/v0/email/send-payment", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .header("Content-Type", "application/json")
-            .json(&email_payment_request)
-            .send()
-            .await?;
+        let payout = async {
+            let request = self
+                .add_headers(HttpRequest::post(&url))
+                .header("Content-Type", "application/json")
+                .json(&email_payment_request)?;
+            let resp = self.send(request).await?;
+
+            self.parse_response(resp)
+        };
 
-        self.parse_response(resp).await
+        self.spend_guard(
+            PaymentDestination::Email(email_payment_request.email.clone()),
+            || email_payment_request.amount.parse().ok(),
+            payout,
+        )
+        .await
     }
 
-    pub async fn create_auth_url<T>(&self, challenge: T) -> Result<String>
-    where
-        T: AsRef<str>,
-    {
+    /// Pays any [`PaymentDestination`] by dispatching to the matching endpoint.
+    /// `amount` is in millisatoshis and ignored for invoices, `comment` is ignored for keysend.
+    ///
+    /// A destination parsed from `user@domain` is a Lightning Address, the string alone cannot
+    /// tell it from an email. Parse `mailto:user@domain` to send to an email instead.
+    /// LNURLs are paid through [`pay_lnurl`](Self::pay_lnurl).
+    pub async fn pay(
+        &self,
+        destination: &PaymentDestination,
+        amount: &str,
+        comment: &str,
+    ) -> Result<PaymentOutcome> {
+        self.pay_with_internal_id(destination, amount, comment, None)
+            .await
+    }
+
+    pub(crate) async fn pay_with_internal_id(
+        &self,
+        destination: &PaymentDestination,
+        amount: &str,
+        comment: &str,
+        internal_id: Option<String>,
+    ) -> Result<PaymentOutcome> {
+        let no_data = || ErrorMsg::NoResponseData(format!("paying {destination}"));
+
+        match destination {
+            PaymentDestination::Invoice(invoice) => {
+                let payment = Payment {
+                    description: comment.to_owned(),
+                    internal_id: internal_id.unwrap_or_default(),
+                    invoice: invoice.clone(),
+                };
+                let resp = self.pay_invoice(&payment).await?;
+                Ok(PaymentOutcome::Invoice(resp.data.ok_or_else(no_data)?))
+            }
+            PaymentDestination::LnAddress(ln_address) => {
+                let payment = LnPayment {
+                    ln_address: ln_address.parse()?,
+                    amount: amount.to_owned(),
+                    comment: comment.to_owned(),
+                    internal_id,
+                };
+                let resp = self.pay_ln_address(&payment).await?;
+                Ok(PaymentOutcome::LnAddress(resp.data.ok_or_else(no_data)?))
+            }
+            PaymentDestination::Gamertag(gamertag) => {
+                let payment = GamertagPayment {
+                    gamertag: gamertag.clone(),
+                    amount: amount.to_owned(),
+                    description: comment.to_owned(),
+                    internal_id,
+                };
+                let resp = self.pay_gamertag(&payment).await?;
+                Ok(PaymentOutcome::Gamertag(resp.data))
+            }
+            PaymentDestination::Email(email) => {
+                let payment = EmailPaymentReqest {
+                    email: email.clone(),
+                    amount: amount.to_owned(),
+                    comment: comment.to_owned(),
+                    internal_id,
+                };
+                let resp = self.pay_email(&payment).await?;
+                Ok(PaymentOutcome::Email(resp.data))
+            }
+            PaymentDestination::NodePubkey(pubkey) => {
+                let payment = Keysend {
+                    amount: amount.to_owned(),
+                    pubkey: pubkey.clone(),
+                    internal_id,
+                    ..Default::default()
+                };
+                let resp = self.keysend(&payment).await?;
+                Ok(PaymentOutcome::Keysend(resp.data.ok_or_else(no_data)?))
+            }
+            PaymentDestination::Lnurl(lnurl) => {
+                let amount = amount.parse().map_err(|_| {
+                    ErrorMsg::BadPayloadData(format!("amount {amount} paying {lnurl}"))
+                })?;
+                let payment = Payment {
+                    description: comment.to_owned(),
+                    internal_id: internal_id.unwrap_or_default(),
+                    invoice: self.fetch_lnurl_invoice(lnurl, amount, comment).await?,
+                };
+                let resp = self.pay_invoice(&payment).await?;
+                Ok(PaymentOutcome::Invoice(resp.data.ok_or_else(no_data)?))
+            }
+        }
+    }
+
+    /// Pays an LNURL-pay link, `amount` is in millisatoshis
+    pub async fn pay_lnurl(
+        &self,
+        lnurl: &str,
+        amount: u64,
+        comment: &str,
+    ) -> Result<PaymentInvoiceResponse> {
+        let payment = Payment {
+            description: comment.to_owned(),
+            invoice: self.fetch_lnurl_invoice(lnurl, amount, comment).await?,
+            ..Default::default()
+        };
+        self.pay_invoice(&payment).await
+    }
+
+    /// Asks the service behind an LNURL-pay link for an invoice of `amount` millisatoshis,
+    /// after checking `amount` and `comment` against the limits it advertises.
+    /// The service is called directly through the client's transport, without the apikey.
+    pub async fn fetch_lnurl_invoice(
+        &self,
+        lnurl: &str,
+        amount: u64,
+        comment: &str,
+    ) -> Result<String> {
+        let bad = |reason: &str| ErrorMsg::BadLnurl(format!("{lnurl}: {reason}"));
+
+        let resp = self.send(HttpRequest::get(decode_lnurl(lnurl)?)).await?;
+        if !resp.is_success() {
+            return Err(bad(&format!("status {}", resp.status)).into());
+        }
+        let params: LnValidateMetadata = parse_lnurl_response(&resp.body)?;
+        if params.tag.as_deref() != Some("payRequest") {
+            return Err(ErrorMsg::UnsupportedDestination(lnurl.to_owned()).into());
+        }
+        params.check_payment(amount, comment)?;
+
+        let callback = params
+            .callback
+            .as_deref()
+            .ok_or_else(|| bad("no callback"))?;
+        let mut callback = url::Url::parse(callback).map_err(|e| bad(&e.to_string()))?;
+        callback
+            .query_pairs_mut()
+            .append_pair("amount", &amount.to_string());
+        if !comment.is_empty() {
+            callback.query_pairs_mut().append_pair("comment", comment);
+        }
+
+        let resp = self.send(HttpRequest::get(callback)).await?;
+        if !resp.is_success() {
+            return Err(bad(&format!("status {}", resp.status)).into());
+        }
+        let invoice: LnurlPayInvoice = parse_lnurl_response(&resp.body)?;
+        match invoice_amount_msats(&invoice.pr) {
+            Some(invoiced) if invoiced == amount => Ok(invoice.pr),
+            _ => Err(bad("invoice amount does not match the requested amount").into()),
+        }
+    }
+
+    /// Authorize url for the challenge of `pkce`, keep `pkce` for the token step
+    pub async fn create_auth_url(&self, pkce: &PKCE) -> Result<String> {
         let url = format!("{}/v1/oauth2/authorize", &self.domain);
 
-        let auth_url = self
-            .reqw_cli
-            .get(url)
-            .header("Content-Type", "application/json")
-            .query(&[("client_id", &self.oauth.client_id)])
-            .query(&[("response_type", "code")])
-            .query(&[("redirect_uri", &self.oauth.redirect_uri)])
-            .query(&[("code_challenge_method", "S256")])
-            .query(&[("code_challenge", challenge.as_ref())])
-            .query(&[("scope", &self.oauth.scope)])
-            .query(&[("state", &self.oauth.state)])
-            .build()
-            .unwrap()
-            .url()
-            .to_string();
+        let auth_url = url::Url::parse_with_params(
+            &url,
+            [
+                ("client_id", self.oauth.client_id.as_str()),
+                ("response_type", "code"),
+                ("redirect_uri", &self.oauth.redirect_uri),
+                ("code_challenge_method", pkce.method.as_str()),
+                ("code_challenge", &pkce.challenge),
+                ("scope", &self.oauth.scope),
+                ("state", &self.oauth.state),
+            ],
+        )
+        .map_err(|e| ErrorMsg::BadUrl(format!("{url}: {e}")))?
+        .to_string();
 
         AuthURL::new(&auth_url).validate()?;
 
@@ -443,15 +970,10 @@ impl ZebedeeClient {
 
         let url = format!("{}/v1/oauth2/token", &self.domain);
 
-        let resp = self
-            .reqw_cli
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await?;
+        let request = HttpRequest::post(&url).json(&payload)?;
+        let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 
     /// In order to fetch a new accessToken for a given ZBD User, make sure to use the refreshToken using the token endpoint.
@@ -463,15 +985,10 @@ impl ZebedeeClient {
         payload.validate()?;
 
         let url = format!("{}/v1/oauth2/token", &self.domain);
-        let resp = self
-            .reqw_cli
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await?;
+        let request = HttpRequest::post(&url).json(&payload)?;
+        let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 
     /// You can use this API endpoint to fetch information about a given ZBD User, granted you can pass the provided accessToken.
@@ -484,13 +1001,12 @@ impl ZebedeeClient {
 
         let url = format!("{}/v1/oauth2/user", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.get(&url))
-            .header("usertoken", token.as_ref())
-            .send()
-            .await?;
+        let request = self
+            .add_headers(HttpRequest::get(&url))
+            .header("usertoken", token.as_ref());
+        let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 
     /// You can use this API endpoint to fetch information about a given ZBD User's Wallet, granted you can pass the provided accessToken.
@@ -503,29 +1019,34 @@ impl ZebedeeClient {
 
         let url = format!("{}/v1/oauth2/wallet", &self.domain);
 
-        let resp = self
-            .add_headers(self.reqw_cli.get(&url))
-            .header("usertoken", token.as_ref())
-            .send()
-            .await?;
+        let request = self
+            .add_headers(HttpRequest::get(&url))
+            .header("usertoken", token.as_ref());
+        let resp = self.send(request).await?;
 
-        self.parse_response(resp).await
+        self.parse_response(resp)
     }
 }
 
 #[derive(Default, Clone, Validate, Deserialize, Debug)]
+#[serde(default)]
 pub struct ZebedeeOauth {
     #[validate(length(equal = 36))]
     client_id: String,
-    #[validate(length(equal = 36))]
-    secret: String,
+    #[validate(custom = "crate::secret::length_36")]
+    secret: Secret,
     #[validate(url)]
     redirect_uri: String,
     #[validate(length(equal = 36))]
     state: String,
+    #[serde(default = "default_scope")]
     scope: String,
 }
 
+fn default_scope() -> String {
+    "user".to_owned()
+}
+
 impl ZebedeeOauth {
     fn new(
         client_id: String,
@@ -536,7 +1057,7 @@ impl ZebedeeOauth {
     ) -> Self {
         ZebedeeOauth {
             client_id,
-            secret,
+            secret: secret.into(),
             redirect_uri,
             state,
             scope,
@@ -544,50 +1065,6 @@ impl ZebedeeOauth {
     }
 }
 
-#[derive(Clone, Debug, Validate, Deserialize)]
-pub struct PKCE {
-    #[validate(length(equal = 43))]
-    pub verifier: String,
-    #[validate(length(equal = 43))]
-    pub challenge: String,
-}
-
-impl PKCE {
-    pub fn new(input: [u8; 32]) -> Self {
-        let verifier = base64_url::encode(&input);
-
-        let mut hasher_2 = Sha256::new();
-        hasher_2.update(verifier.clone());
-        let hash_result_2 = hasher_2.finalize();
-        let challenge = base64_url::encode(&hash_result_2);
-
-        let p = PKCE {
-            verifier,
-            challenge,
-        };
-        p.validate().unwrap();
-        p
-    }
-
-    pub fn new_rand() -> Self {
-        let random_bytes = rand::thread_rng().gen::<[u8; 32]>();
-        Self::new(random_bytes)
-    }
-}
-
-impl From<&str> for PKCE {
-    fn from(value: &str) -> Self {
-        let mut hasher = Sha256::new();
-        hasher.update(value);
-        let hash_result: [u8; 32] = hasher
-            .finalize()
-            .as_slice()
-            .try_into()
-            .expect("hashing went wrong from string");
-        Self::new(hash_result)
-    }
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StdResp<T> {
     pub success: bool,
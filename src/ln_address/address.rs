@@ -0,0 +1,104 @@
+use crate::errors::ErrorMsg;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt::Display, str::FromStr};
+use url::Url;
+
+const ZBD_DOMAIN: &str = "zbd.gg";
+
+/// A LUD-16 Lightning Address, parsed with [`str::parse`] without any API call.
+///
+/// The user part is lower-cased and the domain is converted to its ASCII (punycode) form,
+/// so two spellings of the same address compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LightningAddress {
+    user: String,
+    domain: String,
+}
+
+impl LightningAddress {
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// ASCII form of the domain
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// LNURL-pay endpoint of the address, plain http is only used for onion services
+    pub fn well_known_url(&self) -> String {
+        let scheme = match self.domain.ends_with(".onion") {
+            true => "http",
+            false => "https",
+        };
+        format!(
+            "{scheme}://{}/.well-known/lnurlp/{}",
+            self.domain, self.user
+        )
+    }
+
+    /// Address hosted by ZBD, its user part is a ZBD Gamertag
+    pub fn is_zbd(&self) -> bool {
+        self.domain == ZBD_DOMAIN
+    }
+
+    /// ZBD Gamertag behind an `@zbd.gg` address
+    pub fn gamertag(&self) -> Option<&str> {
+        self.is_zbd().then_some(self.user.as_str())
+    }
+}
+
+impl FromStr for LightningAddress {
+    type Err = ErrorMsg;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = |reason: &str| ErrorMsg::BadLnAddress(s.to_owned(), reason.to_owned());
+        let input = s.trim();
+        let input = match input.get(..10) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("lightning:") => &input[10..],
+            _ => input,
+        };
+
+        let (user, domain) = input.rsplit_once('@').ok_or_else(|| bad("missing @"))?;
+        let user = user.to_lowercase();
+        if user.is_empty()
+            || !user
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.+".contains(c))
+        {
+            return Err(bad("user may only contain a-z, 0-9, '-', '_', '.' and '+'"));
+        }
+
+        if domain.is_empty() || domain.contains(['/', '?', '#', ':', '@', '\\']) {
+            return Err(bad("bad domain"));
+        }
+        let url = Url::parse(&format!("https://{domain}")).map_err(|e| bad(&e.to_string()))?;
+        let domain = match url.domain() {
+            Some(domain) if domain.contains('.') && !domain.split('.').any(str::is_empty) => {
+                domain.to_owned()
+            }
+            _ => return Err(bad("bad domain")),
+        };
+
+        Ok(Self { user, domain })
+    }
+}
+
+impl Display for LightningAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.user, self.domain)
+    }
+}
+
+impl Serialize for LightningAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LightningAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -1,4 +1,8 @@
+mod address;
+mod preflight;
 mod types;
+pub use address::*;
+pub use preflight::*;
 pub use types::*;
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,88 @@
+use crate::{errors::ErrorMsg, ln_address::*, Instant, ZebedeeClient};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Checks Lightning Address payments against the limits the receiving service advertises
+/// before they are sent. Metadata from `validate_ln_address` is cached for `ttl`.
+#[derive(Debug)]
+pub struct LnAddressPreflight {
+    ttl: Duration,
+    cache: Mutex<HashMap<String, (Instant, Arc<LnValidateData>)>>,
+}
+
+impl Default for LnAddressPreflight {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60))
+    }
+}
+
+impl LnAddressPreflight {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn cached(&self, address: &str) -> Option<Arc<LnValidateData>> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        match cache.get(address) {
+            Some((at, data)) if at.elapsed() < self.ttl => Some(data.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, address: &str, data: LnValidateData) -> Arc<LnValidateData> {
+        let data = Arc::new(data);
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.retain(|_, (at, _)| at.elapsed() < self.ttl);
+        cache.insert(address.to_owned(), (Instant::now(), data.clone()));
+        data
+    }
+
+    /// Drops the cached metadata of every address
+    pub fn clear(&self) {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Validates `address` and checks `amount` in millisatoshis and `comment` against its metadata
+    pub(crate) async fn check(
+        &self,
+        client: &ZebedeeClient,
+        address: &LightningAddress,
+        amount: &str,
+        comment: &str,
+    ) -> crate::Result<()> {
+        let address = address.to_string();
+        let amount: u64 = amount
+            .parse()
+            .map_err(|_| ErrorMsg::BadPayloadData(format!("amount {amount} paying {address}")))?;
+
+        let data = match self.cached(&address) {
+            Some(data) => data,
+            None => {
+                let lookup = LnAddress {
+                    address: address.clone(),
+                };
+                let data = client
+                    .validate_ln_address(&lookup)
+                    .await?
+                    .data
+                    .ok_or_else(|| {
+                        ErrorMsg::BadPayloadData(format!("no metadata for {address}"))
+                    })?;
+                self.insert(&address, data)
+            }
+        };
+
+        if !data.valid {
+            return Err(
+                ErrorMsg::BadLnAddress(address, "not a Lightning Address".to_owned()).into(),
+            );
+        }
+        data.metadata.check_payment(amount, comment)
+    }
+}
@@ -1,18 +1,15 @@
-use crate::ZebedeeClient;
-use std::env;
+use crate::{transport::MockTransport, ZebedeeClient};
 
 use super::*;
 
 #[tokio::test]
 async fn test_pay_ln_address() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
     let payment = &LnPayment {
-        ln_address: String::from("miketwenty1@zbd.gg"),
+        ln_address: "miketwenty1@zbd.gg".parse().unwrap(),
         amount: String::from("1000"),
         comment: "rust sdk ln address rest".to_string(),
+        ..Default::default()
     };
     let r = zebedee_client
         .pay_ln_address(payment)
@@ -23,13 +20,10 @@ async fn test_pay_ln_address() {
 }
 #[tokio::test]
 async fn test_fetch_charge_ln_address() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let payment = LnFetchCharge {
-        ln_address: String::from("miketwenty1@zbd.gg"),
+        ln_address: "miketwenty1@zbd.gg".parse().unwrap(),
         amount: String::from("1000"),
         ..Default::default()
     };
@@ -43,10 +37,7 @@ async fn test_fetch_charge_ln_address() {
 
 #[tokio::test]
 async fn test_validate_ln_address() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let ln_address = String::from("andre@zbd.gg");
 
@@ -68,3 +59,90 @@ async fn test_validate_address_format() {
 
     assert_eq!(ln_address.validate(), Ok(()));
 }
+
+#[tokio::test]
+async fn test_ln_address_preflight() {
+    let preflight = LnAddressPreflight::default();
+    let metadata: LnValidateData = serde_json::from_str(
+        r#"{"valid":true,"metadata":{"minSendable":1000,"maxSendable":100000,"commentAllowed":10}}"#,
+    )
+    .unwrap();
+    preflight.insert("satoshi@zbd.gg", metadata);
+
+    // no mock responses, only the cached metadata can answer
+    let zebedee_client = ZebedeeClient::new("apikey")
+        .transport(MockTransport::new())
+        .ln_address_preflight(preflight);
+
+    let payment = LnPayment {
+        ln_address: "Satoshi@zbd.gg".parse().unwrap(),
+        amount: String::from("500000"),
+        comment: String::from("gg"),
+        ..Default::default()
+    };
+    let err = zebedee_client.pay_ln_address(&payment).await.unwrap_err();
+    assert!(err.to_string().contains("above the maximum of 100000"));
+
+    let payment = LnPayment {
+        amount: String::from("1000"),
+        comment: String::from("good game, well played"),
+        ..payment
+    };
+    let err = zebedee_client.pay_ln_address(&payment).await.unwrap_err();
+    assert!(err.to_string().contains("exceeds 10 allowed"));
+
+    let charge = LnFetchCharge {
+        ln_address: "satoshi@zbd.gg".parse().unwrap(),
+        amount: String::from("999"),
+        ..Default::default()
+    };
+    let err = zebedee_client
+        .fetch_charge_ln_address(&charge)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("below the minimum of 1000"));
+}
+
+#[test]
+fn test_lightning_address_parse() {
+    let address: LightningAddress = " lightning:Satoshi@ZBD.gg ".parse().unwrap();
+    assert_eq!(address.user(), "satoshi");
+    assert_eq!(address.domain(), "zbd.gg");
+    assert_eq!(address.to_string(), "satoshi@zbd.gg");
+    assert_eq!(
+        address.well_known_url(),
+        "https://zbd.gg/.well-known/lnurlp/satoshi"
+    );
+    assert_eq!(address.gamertag(), Some("satoshi"));
+
+    let address: LightningAddress = "tip+gg@bücher.example".parse().unwrap();
+    assert_eq!(address.domain(), "xn--bcher-kva.example");
+    assert!(!address.is_zbd());
+    assert_eq!(address.gamertag(), None);
+
+    for bad in [
+        "satoshi",
+        "@zbd.gg",
+        "sat oshi@zbd.gg",
+        "satoshi@zbd",
+        "satoshi@zbd.gg/x",
+        "satoshi@1.2.3.4",
+    ] {
+        assert!(bad.parse::<LightningAddress>().is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn test_lightning_address_serde() {
+    let payment: LnPayment =
+        serde_json::from_str(r#"{"lnAddress":"Andre@zbd.gg","amount":"1000","comment":"gg"}"#)
+            .unwrap();
+    assert_eq!(payment.ln_address.to_string(), "andre@zbd.gg");
+    assert!(serde_json::to_string(&payment)
+        .unwrap()
+        .contains(r#""lnAddress":"andre@zbd.gg""#));
+    assert!(serde_json::from_str::<LnPayment>(
+        r#"{"lnAddress":"andre","amount":"1000","comment":"gg"}"#
+    )
+    .is_err());
+}
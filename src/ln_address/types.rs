@@ -1,4 +1,8 @@
-use crate::StdResp;
+use crate::{
+    ids::{TransactionId, WalletId},
+    ln_address::LightningAddress,
+    StdResp,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -22,13 +26,13 @@ impl LnAddress {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LnPayerData {
     pub name: HashMap<String, bool>,
     pub identifier: HashMap<String, bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LnValidateMetadata {
     #[serde(rename = "minSendable")]
     pub min_sendable: Option<u64>,
@@ -44,7 +48,7 @@ pub struct LnValidateMetadata {
     pub disposable: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LnValidateData {
     pub valid: bool,
     pub metadata: LnValidateMetadata,
@@ -64,7 +68,7 @@ pub struct LnFetchChargeData {
     pub invoice: LnInvoice,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LnSendPaymentData {
     pub id: String,
     pub fee: Option<String>,
@@ -74,9 +78,9 @@ pub struct LnSendPaymentData {
     pub status: String,
     pub invoice: String,
     #[serde(rename = "walletId")]
-    pub wallet_id: String,
+    pub wallet_id: WalletId,
     #[serde(rename = "transactionId")]
-    pub transaction_id: String,
+    pub transaction_id: TransactionId,
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(rename = "processedAt")]
@@ -91,17 +95,25 @@ pub struct LnSendPaymentData {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LnPayment {
     #[serde(rename = "lnAddress")]
-    pub ln_address: String,
+    pub ln_address: LightningAddress,
     pub amount: String,
     pub comment: String,
+    /// Your own reference for the payment, echoed back in transactions
+    #[serde(
+        rename = "internalId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub internal_id: Option<String>,
 }
 
 impl Default for LnPayment {
     fn default() -> Self {
         LnPayment {
-            ln_address: String::from(""),
+            ln_address: LightningAddress::default(),
             amount: String::from(""),
             comment: String::from("using zebedee rust sdk"),
+            internal_id: None,
         }
     }
 }
@@ -110,7 +122,7 @@ impl Default for LnPayment {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LnFetchCharge {
     #[serde(rename = "lnaddress")]
-    pub ln_address: String,
+    pub ln_address: LightningAddress,
     pub amount: String,
     pub description: String,
 }
@@ -118,7 +130,7 @@ pub struct LnFetchCharge {
 impl Default for LnFetchCharge {
     fn default() -> Self {
         LnFetchCharge {
-            ln_address: String::from(""),
+            ln_address: LightningAddress::default(),
             amount: String::from(""),
             description: String::from("using zebedee rust sdk"),
         }
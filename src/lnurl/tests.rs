@@ -0,0 +1,61 @@
+use super::*;
+use crate::ln_address::LnValidateMetadata;
+
+// LUD-01 example
+const LNURL: &str = "LNURL1DP68GURN8GHJ7UM9WFMXJCM99E3K7MF0V9CXJ0M385EKVCENXC6R2C35XVUKXEFCV5MKVV34X5EKZD3EV56NYD3HXQURZEPEXEJXXEPNXSCRVWFNV9NXZCN9XQ6XYEFHVGCXXCMYXYMNSERXFQ5FNS";
+
+#[test]
+fn test_lnurl_round_trip() {
+    let url = decode_lnurl(LNURL).unwrap();
+    assert_eq!(
+        url.as_str(),
+        "https://service.com/api?q=3fc3645b439ce8e7f2553a69e5267081d96dcd340693afabe04be7b0ccd178df"
+    );
+    assert_eq!(encode_lnurl(url.as_str()).unwrap(), LNURL);
+    assert_eq!(
+        decode_lnurl(&format!("lightning:{}", LNURL.to_lowercase())).unwrap(),
+        url
+    );
+
+    assert_eq!(
+        decode_lnurl("lnurlp://example.com/.well-known/lnurlp/satoshi")
+            .unwrap()
+            .as_str(),
+        "https://example.com/.well-known/lnurlp/satoshi"
+    );
+    assert!(decode_lnurl("lnbc1invalid").is_err());
+}
+
+#[test]
+fn test_lnurl_metadata() {
+    let metadata = LnurlMetadata::parse(
+        r#"[["text/plain","Pay satoshi"],["text/identifier","satoshi@zbd.gg"],["image/png;base64","iVBO"]]"#,
+    )
+    .unwrap();
+    assert_eq!(metadata.description(), Some("Pay satoshi"));
+    assert_eq!(metadata.identifier(), Some("satoshi@zbd.gg"));
+    assert_eq!(metadata.image(), Some("iVBO"));
+    assert_eq!(metadata.long_description(), None);
+}
+
+#[test]
+fn test_lnurl_limits() {
+    let metadata: LnValidateMetadata = serde_json::from_str(
+        r#"{"minSendable":1000,"maxSendable":500000,"commentAllowed":5,"tag":"payRequest"}"#,
+    )
+    .unwrap();
+    assert!(metadata.check_payment(1000, "gg").is_ok());
+    assert!(metadata.check_payment(999, "").is_err());
+    assert!(metadata.check_payment(500_001, "").is_err());
+    let err = metadata.check_payment(2000, "good game").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("comment of 9 characters exceeds 5"));
+
+    let withdraw: LnurlWithdrawParams = serde_json::from_str(
+        r#"{"tag":"withdrawRequest","callback":"https://service.com/withdraw","k1":"abc","minWithdrawable":1000,"maxWithdrawable":10000}"#,
+    )
+    .unwrap();
+    assert!(withdraw.check_amount(10_000).is_ok());
+    assert!(withdraw.check_amount(10_001).is_err());
+}
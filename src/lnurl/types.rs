@@ -0,0 +1,224 @@
+use crate::{errors::ErrorMsg, ln_address::LnValidateMetadata};
+use bech32::{Bech32, Hrp};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt::Display;
+use url::Url;
+
+const LNURL_HRP: &str = "lnurl";
+
+/// Bech32 encodes `url` as an upper-case LNURL, the case QR codes store most compactly
+pub fn encode_lnurl(url: &str) -> crate::Result<String> {
+    let url = Url::parse(url).map_err(|e| ErrorMsg::BadLnurl(format!("{url}: {e}")))?;
+    let hrp = Hrp::parse_unchecked(LNURL_HRP);
+    bech32::encode_upper::<Bech32>(hrp, url.as_str().as_bytes())
+        .map_err(|e| ErrorMsg::BadLnurl(e.to_string()).into())
+}
+
+/// Decodes a bech32 LNURL, with or without a `lightning:` prefix.
+/// LUD-17 `lnurlp://`, `lnurlw://`, `lnurlc://` and `keyauth://` urls are turned back into https urls.
+pub fn decode_lnurl(lnurl: &str) -> crate::Result<Url> {
+    let input = lnurl.trim();
+    let bad = |reason: String| ErrorMsg::BadLnurl(format!("{input}: {reason}"));
+    let input = match input.get(..10) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("lightning:") => &input[10..],
+        _ => input,
+    };
+
+    if let Some((scheme, rest)) = input.split_once("://") {
+        if !["lnurlp", "lnurlw", "lnurlc", "keyauth"]
+            .contains(&scheme.to_ascii_lowercase().as_str())
+        {
+            return Err(bad(format!("unknown scheme {scheme}")).into());
+        }
+        let host = rest.split(['/', '?', ':']).next().unwrap_or_default();
+        let scheme = if host.ends_with(".onion") {
+            "http"
+        } else {
+            "https"
+        };
+        return Url::parse(&format!("{scheme}://{rest}")).map_err(|e| bad(e.to_string()).into());
+    }
+
+    let (hrp, data) = bech32::decode(input).map_err(|e| bad(e.to_string()))?;
+    if !hrp.as_str().eq_ignore_ascii_case(LNURL_HRP) {
+        return Err(bad(format!("unexpected prefix {hrp}")).into());
+    }
+    let url = String::from_utf8(data).map_err(|e| bad(e.to_string()))?;
+    Url::parse(&url).map_err(|e| bad(e.to_string()).into())
+}
+
+/// Invoice returned by an LNURL-pay callback, LUD-06
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LnurlPayInvoice {
+    /// Bolt 11 invoice
+    pub pr: String,
+}
+
+#[derive(Deserialize)]
+struct LnurlStatus {
+    status: Option<String>,
+    reason: Option<String>,
+}
+
+/// Parses the JSON answer of an LNURL service, turning `{"status": "ERROR"}` answers into errors
+pub(crate) fn parse_lnurl_response<T: DeserializeOwned>(body: &[u8]) -> crate::Result<T> {
+    if let Ok(LnurlStatus {
+        status: Some(status),
+        reason,
+    }) = serde_json::from_slice(body)
+    {
+        if status.eq_ignore_ascii_case("error") {
+            let reason = reason.unwrap_or_else(|| "no reason given".to_owned());
+            return Err(ErrorMsg::BadLnurl(reason).into());
+        }
+    }
+    Ok(serde_json::from_slice(body)?)
+}
+
+/// Entries of the LNURL-pay `metadata` string, a JSON array of `[mime type, content]` pairs
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LnurlMetadata {
+    pub entries: Vec<(String, String)>,
+}
+
+impl LnurlMetadata {
+    pub fn parse(metadata: &str) -> crate::Result<Self> {
+        // entries other than [string, string] pairs are allowed by LUD-06 and skipped
+        let raw: Vec<Vec<serde_json::Value>> = serde_json::from_str(metadata)?;
+        let entries = raw
+            .into_iter()
+            .filter_map(|entry| match entry.as_slice() {
+                [mime, content, ..] => {
+                    Some((mime.as_str()?.to_owned(), content.as_str()?.to_owned()))
+                }
+                _ => None,
+            })
+            .collect();
+        Ok(Self { entries })
+    }
+
+    /// Content of the first entry with this mime type
+    pub fn get(&self, mime: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(m, _)| m == mime)
+            .map(|(_, content)| content.as_str())
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.get("text/plain")
+    }
+
+    pub fn long_description(&self) -> Option<&str> {
+        self.get("text/long-desc")
+    }
+
+    /// Base64 encoded image, png or jpeg
+    pub fn image(&self) -> Option<&str> {
+        self.get("image/png;base64")
+            .or_else(|| self.get("image/jpeg;base64"))
+    }
+
+    /// Lightning Address or email the payment is for
+    pub fn identifier(&self) -> Option<&str> {
+        self.get("text/identifier")
+            .or_else(|| self.get("text/email"))
+    }
+}
+
+/// LNURL-withdraw parameters returned by the service behind a withdraw LNURL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LnurlWithdrawParams {
+    pub tag: String,
+    pub callback: String,
+    pub k1: String,
+    #[serde(rename = "minWithdrawable")]
+    pub min_withdrawable: u64,
+    #[serde(rename = "maxWithdrawable")]
+    pub max_withdrawable: u64,
+    #[serde(rename = "defaultDescription", default)]
+    pub default_description: String,
+}
+
+impl LnurlWithdrawParams {
+    /// Checks that `amount` millisatoshis can be withdrawn
+    pub fn check_amount(&self, amount: u64) -> crate::Result<()> {
+        check_range(
+            amount,
+            Some(self.min_withdrawable),
+            Some(self.max_withdrawable),
+        )
+    }
+}
+
+impl LnValidateMetadata {
+    /// Parsed `metadata` entries, empty when the service sent none
+    pub fn lnurl_metadata(&self) -> crate::Result<LnurlMetadata> {
+        match &self.metadata {
+            Some(metadata) => LnurlMetadata::parse(metadata),
+            None => Ok(LnurlMetadata::default()),
+        }
+    }
+
+    /// Checks that `amount` millisatoshis and `comment` fit the advertised limits
+    pub fn check_payment(&self, amount: u64, comment: &str) -> crate::Result<()> {
+        check_range(amount, self.min_sendable, self.max_sendable)?;
+
+        let length = comment.chars().count() as u64;
+        let allowed = self.comment_allowed.unwrap_or(0);
+        if length > allowed {
+            return Err(
+                ErrorMsg::LnurlLimit(LnurlViolation::CommentTooLong { length, allowed }).into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn check_range(amount: u64, min: Option<u64>, max: Option<u64>) -> crate::Result<()> {
+    if let Some(min) = min.filter(|min| amount < *min) {
+        return Err(ErrorMsg::LnurlLimit(LnurlViolation::BelowMinimum { amount, min }).into());
+    }
+    if let Some(max) = max.filter(|max| amount > *max) {
+        return Err(ErrorMsg::LnurlLimit(LnurlViolation::AboveMaximum { amount, max }).into());
+    }
+    Ok(())
+}
+
+/// Amount or comment outside of what an LNURL service advertises. Amounts are in millisatoshis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LnurlViolation {
+    BelowMinimum {
+        amount: u64,
+        min: u64,
+    },
+    AboveMaximum {
+        amount: u64,
+        max: u64,
+    },
+    /// Comment length in characters, `allowed` is 0 when comments are not accepted
+    CommentTooLong {
+        length: u64,
+        allowed: u64,
+    },
+}
+
+impl Display for LnurlViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LnurlViolation::BelowMinimum { amount, min } => {
+                write!(f, "{amount} msats is below the minimum of {min} msats")
+            }
+            LnurlViolation::AboveMaximum { amount, max } => {
+                write!(f, "{amount} msats is above the maximum of {max} msats")
+            }
+            LnurlViolation::CommentTooLong { length, allowed } => {
+                write!(
+                    f,
+                    "comment of {length} characters exceeds {allowed} allowed"
+                )
+            }
+        }
+    }
+}
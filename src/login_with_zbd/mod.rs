@@ -1,4 +1,8 @@
+mod pkce;
 mod types;
+pub use pkce::*;
 pub use types::*;
 #[cfg(test)]
 mod tests;
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests;
@@ -0,0 +1,162 @@
+use crate::{
+    errors::{ErrorMsg, ZebedeeError},
+    secret::Secret,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use validator::{Validate, ValidationError};
+
+/// Characters allowed in a verifier, RFC 7636 section 4.1
+const UNRESERVED: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
+pub const MIN_VERIFIER_LEN: usize = 43;
+pub const MAX_VERIFIER_LEN: usize = 128;
+
+fn is_verifier(verifier: &str) -> bool {
+    (MIN_VERIFIER_LEN..=MAX_VERIFIER_LEN).contains(&verifier.len())
+        && verifier.bytes().all(|b| UNRESERVED.contains(&b))
+}
+
+fn bad_verifier() -> ZebedeeError {
+    ErrorMsg::BadPkce(format!(
+        "verifier must be {MIN_VERIFIER_LEN} to {MAX_VERIFIER_LEN} characters of A-Z a-z 0-9 - . _ ~"
+    ))
+    .into()
+}
+
+/// `#[validate(custom = "...")]` check for verifiers
+pub(crate) fn valid_verifier(verifier: &Secret) -> Result<(), ValidationError> {
+    match is_verifier(verifier.expose_secret()) {
+        true => Ok(()),
+        false => Err(ValidationError::new("pkce_verifier")),
+    }
+}
+
+/// Compares without stopping at the first difference, so timing does not reveal the match length
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// `code_challenge_method` of a PKCE pair
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PkceMethod {
+    #[default]
+    S256,
+    /// The challenge is the verifier itself, so anyone who sees the authorize url can
+    /// redeem the code. Needs the `insecure-pkce-plain` feature.
+    #[cfg(feature = "insecure-pkce-plain")]
+    #[serde(rename = "plain")]
+    Plain,
+}
+
+impl PkceMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PkceMethod::S256 => "S256",
+            #[cfg(feature = "insecure-pkce-plain")]
+            PkceMethod::Plain => "plain",
+        }
+    }
+
+    /// Challenge derived from `verifier`
+    pub fn challenge(&self, verifier: &str) -> String {
+        match self {
+            PkceMethod::S256 => base64_url::encode(&Sha256::digest(verifier)),
+            #[cfg(feature = "insecure-pkce-plain")]
+            PkceMethod::Plain => verifier.to_owned(),
+        }
+    }
+
+    /// Whether `verifier` is well formed and derives `challenge`
+    pub fn verify(&self, verifier: &str, challenge: &str) -> bool {
+        is_verifier(verifier)
+            && constant_time_eq(self.challenge(verifier).as_bytes(), challenge.as_bytes())
+    }
+}
+
+/// Verifier and challenge for one authorization, RFC 7636.
+///
+/// Serializes with the verifier in clear, so a pending pair can be stored between the
+/// authorize and token steps. Call `validate` on pairs loaded back from storage.
+#[derive(Clone, Debug, Validate, Serialize, Deserialize)]
+pub struct PKCE {
+    #[validate(custom = "valid_verifier")]
+    pub verifier: Secret,
+    #[validate(length(min = 43, max = 128))]
+    pub challenge: String,
+    #[serde(default)]
+    pub method: PkceMethod,
+}
+
+impl PKCE {
+    /// 32 bytes always encode to a valid 43 character verifier
+    pub fn new(input: [u8; 32]) -> Self {
+        let verifier = base64_url::encode(&input);
+        PKCE {
+            challenge: PkceMethod::S256.challenge(&verifier),
+            verifier: verifier.into(),
+            method: PkceMethod::S256,
+        }
+    }
+
+    /// S256 pair from raw bytes, 32 to 96 bytes encode to a 43 to 128 character verifier
+    pub fn try_new(input: &[u8]) -> crate::Result<Self> {
+        Self::from_verifier(base64_url::encode(input))
+    }
+
+    /// S256 pair for a verifier of 43 to 128 unreserved characters
+    pub fn from_verifier(verifier: impl Into<Secret>) -> crate::Result<Self> {
+        Self::with_method(verifier.into(), PkceMethod::S256)
+    }
+
+    /// Pair using the `plain` method, only for authorization servers that cannot do S256
+    #[cfg(feature = "insecure-pkce-plain")]
+    pub fn plain_insecure(verifier: impl Into<Secret>) -> crate::Result<Self> {
+        Self::with_method(verifier.into(), PkceMethod::Plain)
+    }
+
+    fn with_method(verifier: Secret, method: PkceMethod) -> crate::Result<Self> {
+        if !is_verifier(verifier.expose_secret()) {
+            return Err(bad_verifier());
+        }
+        Ok(PKCE {
+            challenge: method.challenge(verifier.expose_secret()),
+            verifier,
+            method,
+        })
+    }
+
+    pub fn new_rand() -> Self {
+        let random_bytes = rand::thread_rng().gen::<[u8; 32]>();
+        Self::new(random_bytes)
+    }
+
+    /// S256 pair with a random verifier of `len` unreserved characters, 43 to 128
+    pub fn new_rand_len(len: usize) -> crate::Result<Self> {
+        if !(MIN_VERIFIER_LEN..=MAX_VERIFIER_LEN).contains(&len) {
+            return Err(bad_verifier());
+        }
+        let mut rng = rand::thread_rng();
+        let verifier: String = (0..len)
+            .map(|_| UNRESERVED[rng.gen_range(0..UNRESERVED.len())] as char)
+            .collect();
+        Self::from_verifier(verifier)
+    }
+
+    /// Checks a verifier received at the token step against the stored S256 challenge
+    pub fn verify(verifier: &str, challenge: &str) -> bool {
+        PkceMethod::S256.verify(verifier, challenge)
+    }
+}
+
+/// Derives the pair from a seed string, which must not be empty
+impl TryFrom<&str> for PKCE {
+    type Error = ZebedeeError;
+
+    fn try_from(value: &str) -> crate::Result<Self> {
+        if value.is_empty() {
+            return Err(ErrorMsg::BadPkce("empty seed".to_owned()).into());
+        }
+        Ok(Self::new(Sha256::digest(value).into()))
+    }
+}
@@ -1,10 +1,10 @@
 use crate::ZebedeeClient;
-use crate::PKCE;
-use std::env;
+use crate::{PkceMethod, PKCE};
+use validator::Validate;
 
 #[tokio::test]
 async fn test_create_challenge_from_string() {
-    let c = PKCE::from("hellomynameiswhat");
+    let c = PKCE::try_from("hellomynameiswhat").unwrap();
 
     assert_eq!(
         c.challenge,
@@ -21,49 +21,21 @@ async fn test_create_challenge_rand() {
 
 #[tokio::test]
 async fn test_create_oauth_auth_url() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let oauth_client_id: String = env::var("ZBD_OAUTH_CLIENT_ID").unwrap();
-    let oauth_secret: String = env::var("ZBD_OAUTH_SECRET").unwrap();
-    let redirect_uri: String = env::var("ZBD_REDIRECT_URI").unwrap();
-    let state: String = env::var("ZBD_OAUTH_STATE").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv).oauth(
-        oauth_client_id,
-        oauth_secret,
-        redirect_uri,
-        state,
-        "user".to_owned(),
-    );
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
-    let c = PKCE::from("hellomynameiswhat");
-    let r = zebedee_client.create_auth_url(&c.challenge);
+    let c = PKCE::try_from("hellomynameiswhat").unwrap();
+    let r = zebedee_client.create_auth_url(&c);
 
     assert!(r.await.is_ok());
 }
 
 #[tokio::test]
 async fn test_fetch_token() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let oauth_client_id: String = env::var("ZBD_OAUTH_CLIENT_ID").unwrap();
-    let oauth_secret: String = env::var("ZBD_OAUTH_SECRET").unwrap();
-    let redirect_uri: String = env::var("ZBD_REDIRECT_URI").unwrap();
-    let state: String = env::var("ZBD_OAUTH_STATE").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv).oauth(
-        oauth_client_id,
-        oauth_secret,
-        redirect_uri,
-        state,
-        "user".to_owned(),
-    );
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
-    let c = PKCE::from("hellomynameiswhat");
+    let c = PKCE::try_from("hellomynameiswhat").unwrap();
     let fake_code = "xxx11xx1-xxxx-xxxx-xxx1-1xx11xx111xx";
-    let r = zebedee_client.fetch_token(fake_code, c.verifier);
+    let r = zebedee_client.fetch_token(fake_code, c.verifier.expose_secret());
     //let mut i = String::from("");
     let i = match r.await {
         Err(e) => e.to_string(),
@@ -75,21 +47,7 @@ async fn test_fetch_token() {
 
 #[tokio::test]
 async fn test_refresh_token() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let oauth_client_id: String = env::var("ZBD_OAUTH_CLIENT_ID").unwrap();
-    let oauth_secret: String = env::var("ZBD_OAUTH_SECRET").unwrap();
-    let redirect_uri: String = env::var("ZBD_REDIRECT_URI").unwrap();
-    let state: String = env::var("ZBD_OAUTH_STATE").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv).oauth(
-        oauth_client_id,
-        oauth_secret,
-        redirect_uri,
-        state,
-        "user".to_owned(),
-    );
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let fake_refresh_token = "xxx11xx1-xxxx-xxxx-xxx1-1xx11xx111xx";
     let r = zebedee_client.refresh_token(fake_refresh_token);
@@ -103,21 +61,7 @@ async fn test_refresh_token() {
 
 #[tokio::test]
 async fn test_fetch_user_data() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let oauth_client_id: String = env::var("ZBD_OAUTH_CLIENT_ID").unwrap();
-    let oauth_secret: String = env::var("ZBD_OAUTH_SECRET").unwrap();
-    let redirect_uri: String = env::var("ZBD_REDIRECT_URI").unwrap();
-    let state: String = env::var("ZBD_OAUTH_STATE").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv).oauth(
-        oauth_client_id,
-        oauth_secret,
-        redirect_uri,
-        state,
-        "user".to_owned(),
-    );
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let fake_refresh_token = String::from("eyAAAAyomommagotocollegeAAAxxxXXAAAAasdfasdfsas");
     let r = zebedee_client.fetch_user_data(fake_refresh_token);
@@ -130,21 +74,7 @@ async fn test_fetch_user_data() {
 
 #[tokio::test]
 async fn test_fetch_user_wallet_data() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let oauth_client_id: String = env::var("ZBD_OAUTH_CLIENT_ID").unwrap();
-    let oauth_secret: String = env::var("ZBD_OAUTH_SECRET").unwrap();
-    let redirect_uri: String = env::var("ZBD_REDIRECT_URI").unwrap();
-    let state: String = env::var("ZBD_OAUTH_STATE").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv).oauth(
-        oauth_client_id,
-        oauth_secret,
-        redirect_uri,
-        state,
-        "user,wallet".to_owned(),
-    );
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let fake_refresh_token = String::from("eyAAAAyomommagotocollegeAAAxxxXXAAAAasdfasdfsas");
     let r = zebedee_client.fetch_user_wallet_data(fake_refresh_token);
@@ -156,3 +86,194 @@ async fn test_fetch_user_wallet_data() {
 
     assert!(i.contains("No Message Returned"));
 }
+
+const UUID: &str = "9c4e1dc6-2f5b-4bde-9b0f-5a3e8f1d2c7a";
+
+#[test]
+fn test_try_domain_rejects_malformed_domains() {
+    for bad in [
+        "",
+        "api.zebedee.io",
+        "not a url",
+        "ftp://api.zebedee.io",
+        "mailto:dev@zebedee.io",
+        "http://",
+    ] {
+        let err = ZebedeeClient::new("key").try_domain(bad).unwrap_err();
+        assert!(err.to_string().starts_with("Bad url"), "{bad}: {err}");
+    }
+    assert!(ZebedeeClient::new("key")
+        .try_domain("http://localhost:3000")
+        .is_ok());
+}
+
+#[test]
+fn test_try_oauth_rejects_malformed_redirect_uris() {
+    let try_oauth = |redirect_uri: &str| {
+        ZebedeeClient::new("key").try_oauth(
+            UUID.to_owned(),
+            UUID.to_owned(),
+            redirect_uri.to_owned(),
+            UUID.to_owned(),
+            "user".to_owned(),
+        )
+    };
+    for bad in [
+        "",
+        "callback",
+        "/oauth/callback",
+        "http://exa mple.com",
+        "https://",
+    ] {
+        assert!(try_oauth(bad).is_err(), "{bad}");
+    }
+    assert!(try_oauth("https://game.example/callback").is_ok());
+    assert!(ZebedeeClient::new("key")
+        .try_oauth(
+            "id".to_owned(),
+            UUID.to_owned(),
+            "https://game.example/callback".to_owned(),
+            UUID.to_owned(),
+            "user".to_owned(),
+        )
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_oauth_calls_return_errors_on_malformed_input() {
+    let pkce = PKCE::new_rand();
+    for domain in ["", "not a url", "http://exa mple.com"] {
+        assert!(ZebedeeClient::new("key").try_domain(domain).is_err());
+        // the unchecked setter still fails, only later
+        #[allow(deprecated)]
+        let client = ZebedeeClient::new("key").domain(domain.to_owned());
+        assert!(client.create_auth_url(&pkce).await.is_err(), "{domain}");
+    }
+
+    let client = ZebedeeClient::new("key")
+        .try_domain("https://zbd.test")
+        .unwrap()
+        .oauth(
+            UUID.to_owned(),
+            UUID.to_owned(),
+            "not a url".to_owned(),
+            UUID.to_owned(),
+            "user".to_owned(),
+        );
+    // rejected before anything is sent
+    assert!(client.fetch_token(UUID, "short").await.is_err());
+    assert!(client.refresh_token(UUID).await.is_err());
+}
+
+#[test]
+fn test_pkce_try_constructors() {
+    assert!(PKCE::try_from("").is_err());
+    let c = PKCE::try_new(&[7; 32]).unwrap();
+    assert_eq!(c.verifier.expose_secret().len(), 43);
+    assert_eq!(c.challenge, PKCE::new([7; 32]).challenge);
+}
+
+// RFC 7636 appendix B
+const RFC_VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+const RFC_CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+#[test]
+fn test_pkce_verifier_lengths() {
+    let c = PKCE::from_verifier(RFC_VERIFIER).unwrap();
+    assert_eq!(c.challenge, RFC_CHALLENGE);
+    assert_eq!(c.method, PkceMethod::S256);
+
+    for len in [43, 64, 128] {
+        let c = PKCE::new_rand_len(len).unwrap();
+        assert_eq!(c.verifier.expose_secret().len(), len);
+        assert!(c.validate().is_ok());
+        assert!(PKCE::verify(c.verifier.expose_secret(), &c.challenge));
+    }
+    assert!(PKCE::new_rand_len(42).is_err());
+    assert!(PKCE::new_rand_len(129).is_err());
+
+    assert_eq!(
+        PKCE::try_new(&[1; 96])
+            .unwrap()
+            .verifier
+            .expose_secret()
+            .len(),
+        128
+    );
+    assert!(PKCE::try_new(&[1; 31]).is_err());
+    assert!(PKCE::try_new(&[1; 97]).is_err());
+
+    let long = "a".repeat(129);
+    for bad in ["short", long.as_str(), &format!("{}+", &RFC_VERIFIER[1..])] {
+        let err = PKCE::from_verifier(bad).unwrap_err();
+        assert!(!err.to_string().contains(bad));
+    }
+}
+
+#[test]
+fn test_pkce_verify() {
+    assert!(PKCE::verify(RFC_VERIFIER, RFC_CHALLENGE));
+    assert!(!PKCE::verify(RFC_VERIFIER, &RFC_CHALLENGE[1..]));
+    assert!(!PKCE::verify(&RFC_VERIFIER[1..], RFC_CHALLENGE));
+    assert!(!PKCE::verify(RFC_VERIFIER, RFC_VERIFIER));
+    assert!(!PkceMethod::S256.verify("", ""));
+}
+
+#[test]
+fn test_pkce_round_trips_through_serde() {
+    let c = PKCE::new_rand_len(100).unwrap();
+    let json = serde_json::to_string(&c).unwrap();
+    assert!(json.contains(r#""method":"S256""#));
+
+    let back: PKCE = serde_json::from_str(&json).unwrap();
+    assert!(back.validate().is_ok());
+    assert_eq!(back.verifier.expose_secret(), c.verifier.expose_secret());
+    assert!(PKCE::verify(back.verifier.expose_secret(), &c.challenge));
+
+    // pairs stored before the method was recorded are S256
+    let old: PKCE = serde_json::from_value(serde_json::json!({
+        "verifier": RFC_VERIFIER,
+        "challenge": RFC_CHALLENGE,
+    }))
+    .unwrap();
+    assert_eq!(old.method, PkceMethod::S256);
+    #[cfg(not(feature = "insecure-pkce-plain"))]
+    assert!(serde_json::from_str::<PkceMethod>(r#""plain""#).is_err());
+}
+
+fn auth_client() -> ZebedeeClient {
+    ZebedeeClient::new("key")
+        .try_domain("https://zbd.test")
+        .unwrap()
+        .oauth(
+            UUID.to_owned(),
+            UUID.to_owned(),
+            "https://game.example/callback".to_owned(),
+            UUID.to_owned(),
+            "user".to_owned(),
+        )
+}
+
+#[tokio::test]
+async fn test_auth_url_carries_the_pkce_method() {
+    let c = PKCE::from_verifier(RFC_VERIFIER).unwrap();
+    let url = auth_client().create_auth_url(&c).await.unwrap();
+    assert!(url.contains("code_challenge_method=S256"), "{url}");
+    assert!(
+        url.contains(&format!("code_challenge={RFC_CHALLENGE}")),
+        "{url}"
+    );
+}
+
+#[cfg(feature = "insecure-pkce-plain")]
+#[tokio::test]
+async fn test_pkce_plain_method() {
+    let c = PKCE::plain_insecure(RFC_VERIFIER).unwrap();
+    assert_eq!(c.challenge, RFC_VERIFIER);
+    assert_eq!(c.method.as_str(), "plain");
+    assert!(PkceMethod::Plain.verify(RFC_VERIFIER, RFC_VERIFIER));
+    assert!(!PKCE::verify(RFC_VERIFIER, RFC_VERIFIER));
+
+    let url = auth_client().create_auth_url(&c).await.unwrap();
+    assert!(url.contains("code_challenge_method=plain"), "{url}");
+}
@@ -1,14 +1,14 @@
-use crate::ZebedeeClient;
+use crate::{secret::Secret, ZebedeeClient};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use validator::Validate;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FetchPostRes {
-    pub access_token: String,
+    pub access_token: Secret,
     pub token_type: String,
     pub expires_in: u32,
-    pub refresh_token: String,
+    pub refresh_token: Secret,
     pub scope: String,
 }
 
@@ -32,12 +32,12 @@ impl<'a> AuthURL<'a> {
 pub struct FetchTokenBody<'a> {
     #[validate(length(equal = 36))]
     pub client_id: Cow<'a, str>,
-    #[validate(length(equal = 36))]
-    pub client_secret: Cow<'a, str>,
-    #[validate(length(equal = 36))]
-    pub code: Cow<'a, str>,
-    #[validate(length(equal = 43))]
-    pub code_verifier: Cow<'a, str>,
+    #[validate(custom = "crate::secret::length_36")]
+    pub client_secret: Secret,
+    #[validate(custom = "crate::secret::length_36")]
+    pub code: Secret,
+    #[validate(custom = "super::pkce::valid_verifier")]
+    pub code_verifier: Secret,
     #[validate(length(min = 1))]
     pub grant_type: Cow<'a, str>,
     #[validate(url)]
@@ -47,12 +47,12 @@ pub struct FetchTokenBody<'a> {
 impl<'a> FetchTokenBody<'a> {
     pub fn new<A, B>(zc: &'a ZebedeeClient, code: A, code_verifier: B) -> Self
     where
-        A: Into<Cow<'a, str>>,
-        B: Into<Cow<'a, str>>,
+        A: Into<Secret>,
+        B: Into<Secret>,
     {
         FetchTokenBody {
             client_id: zc.oauth.client_id.as_str().into(),
-            client_secret: zc.oauth.secret.as_str().into(),
+            client_secret: zc.oauth.secret.clone(),
             code: code.into(),
             code_verifier: code_verifier.into(),
             grant_type: "authorization_code".into(),
@@ -84,10 +84,10 @@ impl<'a> FetchTokenBody<'a> {
 
 #[derive(Serialize, Validate, Deserialize, Debug)]
 pub struct FetchAccessTokenRes {
-    pub access_token: String,
+    pub access_token: Secret,
     pub token_type: String,
     pub expires_in: u32,
-    pub refresh_token: String,
+    pub refresh_token: Secret,
     pub refresh_token_expires_in: u32,
     pub scope: String,
 }
@@ -97,10 +97,10 @@ pub struct FetchAccessTokenRes {
 pub struct FetchRefresh<'a> {
     #[validate(length(equal = 36))]
     pub client_id: Cow<'a, str>,
-    #[validate(length(equal = 36))]
-    pub client_secret: Cow<'a, str>,
-    #[validate(length(equal = 36))]
-    pub refresh_token: Cow<'a, str>,
+    #[validate(custom = "crate::secret::length_36")]
+    pub client_secret: Secret,
+    #[validate(custom = "crate::secret::length_36")]
+    pub refresh_token: Secret,
     #[validate(length(min = 1))]
     pub grant_type: Cow<'a, str>,
     #[validate(url)]
@@ -110,11 +110,11 @@ pub struct FetchRefresh<'a> {
 impl<'a> FetchRefresh<'a> {
     pub fn new<T>(zc: &'a ZebedeeClient, refresh_token: T) -> Self
     where
-        T: Into<Cow<'a, str>>,
+        T: Into<Secret>,
     {
         FetchRefresh {
             client_id: zc.oauth.client_id.as_str().into(),
-            client_secret: zc.oauth.secret.as_str().into(),
+            client_secret: zc.oauth.secret.clone(),
             grant_type: "refresh_token".into(),
             redirect_uri: zc.oauth.redirect_uri.as_str().into(),
             refresh_token: refresh_token.into(),
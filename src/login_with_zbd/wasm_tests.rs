@@ -0,0 +1,79 @@
+use crate::{
+    api::{Endpoint, FakeZebedeeApi, ZebedeeApi},
+    ZebedeeClient, PKCE,
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const UUID: &str = "9c4e1dc6-2f5b-4bde-9b0f-5a3e8f1d2c7a";
+
+fn oauth_client() -> ZebedeeClient {
+    ZebedeeClient::new("key").oauth(
+        UUID.to_owned(),
+        UUID.to_owned(),
+        "https://game.example/callback".to_owned(),
+        UUID.to_owned(),
+        "user".to_owned(),
+    )
+}
+
+#[wasm_bindgen_test]
+fn test_pkce_rand_in_browser() {
+    let first = PKCE::new_rand();
+    let second = PKCE::new_rand();
+
+    assert_ne!(
+        first.verifier.expose_secret(),
+        second.verifier.expose_secret()
+    );
+    assert_eq!(first.verifier.expose_secret().len(), 43);
+    assert_eq!(
+        first.challenge,
+        base64_url::encode(&Sha256::digest(first.verifier.expose_secret().as_bytes()))
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_pkce_from_string_in_browser() {
+    let c = PKCE::try_from("hellomynameiswhat").unwrap();
+    assert_eq!(c.challenge, "mBc-M8x_JG5qARgND5Vzx7fPu1EjZlapL_dVg4BjrkU");
+}
+
+#[wasm_bindgen_test]
+async fn test_create_auth_url_in_browser() {
+    let c = PKCE::new_rand();
+    let url = oauth_client().create_auth_url(&c).await.unwrap();
+
+    assert!(url.contains(&format!("code_challenge={}", c.challenge)));
+    assert!(url.contains(&format!("client_id={UUID}")));
+    assert!(url.contains("code_challenge_method=S256"));
+}
+
+#[wasm_bindgen_test]
+async fn test_fetch_token_with_fake_in_browser() {
+    let fake = FakeZebedeeApi::new();
+    fake.respond(
+        Endpoint::FetchToken,
+        &json!({
+            "access_token": "access",
+            "expires_in": 3600,
+            "refresh_token": "refresh",
+            "refresh_token_expires_in": 7200,
+            "scope": "user",
+            "token_type": "Bearer",
+        }),
+    )
+    .unwrap();
+
+    let c = PKCE::new_rand();
+    let api: &dyn ZebedeeApi = &fake;
+    api.fetch_token("code", c.verifier.expose_secret())
+        .await
+        .unwrap();
+
+    let calls = fake.calls_to(Endpoint::FetchToken);
+    assert_eq!(calls[0].args["verifier"], c.verifier.expose_secret());
+}
@@ -1,9 +1,38 @@
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum UnitType {
+    #[default]
     #[serde(rename = "msats")]
     Msats,
     #[serde(rename = "sats")]
     Sats,
 }
+
+impl UnitType {
+    /// Converts an amount in this unit to millisatoshis
+    pub fn to_msats(&self, amount: u64) -> u64 {
+        self.checked_to_msats(amount).unwrap_or(u64::MAX)
+    }
+
+    /// Like `to_msats`, `None` when the result does not fit in a `u64`
+    pub fn checked_to_msats(&self, amount: u64) -> Option<u64> {
+        match self {
+            UnitType::Msats => Some(amount),
+            UnitType::Sats => amount.checked_mul(1000),
+        }
+    }
+}
+
+impl FromStr for UnitType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "msats" | "msat" => Ok(UnitType::Msats),
+            "sats" | "sat" => Ok(UnitType::Sats),
+            _ => Err(format!("unknown unit {s}")),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::StdResp;
+use crate::{ids::PaymentId, StdResp};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -6,9 +6,9 @@ pub type PaymentInvoiceResponse = StdResp<Option<PaymentsData>>;
 pub type FetchPaymentsResponse = StdResp<Option<Vec<PaymentsData>>>;
 pub type FetchOnePaymentsResponse = StdResp<Option<PaymentsData>>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentsData {
-    pub id: String,
+    pub id: PaymentId,
     pub fee: Option<String>,
     pub unit: String,
     pub amount: String,
@@ -25,7 +25,7 @@ pub struct PaymentsData {
 }
 
 /// Use this struct to create a well crafted json body for normal ligthning bolt 11 payments
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Payment {
     pub description: String,
     #[serde(rename = "internalId")]
@@ -42,3 +42,36 @@ impl Default for Payment {
         }
     }
 }
+
+impl Payment {
+    /// Amount encoded in the bolt 11 invoice, in millisatoshis
+    pub fn amount_msats(&self) -> Option<u64> {
+        invoice_amount_msats(&self.invoice)
+    }
+}
+
+/// Reads the amount from the human readable part of a bolt 11 invoice, in millisatoshis.
+/// Returns `None` for invoices without an amount or that cannot be parsed.
+pub fn invoice_amount_msats(invoice: &str) -> Option<u64> {
+    let invoice = invoice.trim().to_ascii_lowercase();
+    let invoice = invoice.strip_prefix("lightning:").unwrap_or(&invoice);
+    let hrp = invoice.get(..invoice.rfind('1')?)?.strip_prefix("ln")?;
+    let amount = ["bcrt", "tbs", "bc", "tb", "sb"]
+        .iter()
+        .find_map(|currency| hrp.strip_prefix(currency))?;
+
+    let (digits, multiplier) = match amount.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&amount[..i], Some(c)),
+        _ => (amount, None),
+    };
+    let value: u64 = digits.parse().ok()?;
+
+    match multiplier {
+        None => value.checked_mul(100_000_000_000),
+        Some('m') => value.checked_mul(100_000_000),
+        Some('u') => value.checked_mul(100_000),
+        Some('n') => value.checked_mul(100),
+        Some('p') if value.is_multiple_of(10) => Some(value / 10),
+        _ => None,
+    }
+}
@@ -0,0 +1,44 @@
+use super::*;
+use crate::{config::Environment, ids::WalletId};
+
+fn wallet_id() -> WalletId {
+    "b904ee02-ec0b-4fd4-b99f-1f2d3d0001a6".parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_register_projects() {
+    let projects = ZebedeeProjects::new()
+        .project("game-a", "apikey-a", Some(wallet_id()))
+        .project("game-b", "apikey-b", None)
+        .environment(Environment::Sandbox);
+
+    assert_eq!(projects.len(), 2);
+    assert_eq!(
+        projects.names().collect::<Vec<_>>(),
+        vec!["game-a", "game-b"]
+    );
+    assert_eq!(
+        projects.client("game-b").unwrap().domain,
+        Environment::Sandbox.url()
+    );
+    assert!(projects.client("game-c").is_err());
+}
+
+#[tokio::test]
+async fn test_transfer_requires_wallet_id() {
+    let projects = ZebedeeProjects::new()
+        .project("game-a", "apikey-a", Some(wallet_id()))
+        .project("game-b", "apikey-b", None);
+
+    let err = projects
+        .transfer("game-a", "game-b", 1000)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("game-b"));
+
+    let err = projects
+        .transfer("game-c", "game-a", 1000)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Unknown project"));
+}
@@ -0,0 +1,201 @@
+use crate::{
+    config::Environment,
+    errors::ErrorMsg,
+    ids::WalletId,
+    internal_transfer::*,
+    transport::{default_transport, HttpTransport},
+    ZebedeeClient,
+};
+use futures::future::try_join_all;
+use std::{collections::BTreeMap, sync::Arc};
+
+/// A single ZBD project registered in [`ZebedeeProjects`]
+#[derive(Clone, Debug)]
+pub struct Project {
+    pub client: ZebedeeClient,
+    /// Project Wallet id, needed to receive internal transfers
+    pub wallet_id: Option<WalletId>,
+}
+
+/// Balances of every registered project, in millisatoshis
+#[derive(Debug, Clone, Default)]
+pub struct ProjectBalances {
+    pub balances: BTreeMap<String, u64>,
+    pub total: u64,
+}
+
+/// Registry of named clients for several ZBD projects sharing one connection pool
+#[derive(Clone, Debug)]
+pub struct ZebedeeProjects {
+    transport: Arc<dyn HttpTransport>,
+    environment: Environment,
+    projects: BTreeMap<String, Project>,
+}
+
+impl Default for ZebedeeProjects {
+    fn default() -> Self {
+        Self {
+            transport: default_transport(),
+            environment: Environment::default(),
+            projects: BTreeMap::new(),
+        }
+    }
+}
+
+impl ZebedeeProjects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shared reqwest client used by every project
+    #[cfg(feature = "reqwest")]
+    pub fn reqw_cli(self, reqw_cli: reqwest::Client) -> Self {
+        self.transport(crate::transport::ReqwestTransport::new(reqw_cli))
+    }
+
+    /// Shared HTTP stack used by every project
+    pub fn transport<T: HttpTransport + 'static>(self, transport: T) -> Self {
+        self.with_transport(Arc::new(transport))
+    }
+
+    /// Shared tower service used by every project, see [`ZebedeeClient::transport_service`]
+    #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+    pub fn transport_service<S>(self, service: S) -> Self
+    where
+        S: tower_service::Service<http::Request<Vec<u8>>, Response = http::Response<Vec<u8>>>
+            + Clone
+            + Send
+            + 'static,
+        S::Error: Into<crate::transport::BoxError>,
+        S::Future: Send,
+    {
+        self.transport(crate::transport::TowerTransport::new(service))
+    }
+
+    fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        for project in self.projects.values_mut() {
+            project.client.transport = transport.clone();
+        }
+        Self { transport, ..self }
+    }
+
+    /// Zebedee REST API environment used by every project
+    pub fn environment(mut self, environment: Environment) -> Self {
+        for project in self.projects.values_mut() {
+            project.client.domain = environment.url().to_owned();
+        }
+        Self {
+            environment,
+            ..self
+        }
+    }
+
+    pub fn project<N, K>(mut self, name: N, apikey: K, wallet_id: Option<WalletId>) -> Self
+    where
+        N: Into<String>,
+        K: AsRef<str>,
+    {
+        self.insert(name, apikey, wallet_id);
+        self
+    }
+
+    /// Registers a project, replacing any project with the same name
+    pub fn insert<N, K>(&mut self, name: N, apikey: K, wallet_id: Option<WalletId>)
+    where
+        N: Into<String>,
+        K: AsRef<str>,
+    {
+        let client = ZebedeeClient::new(apikey.as_ref())
+            .environment(self.environment.clone())
+            .shared_transport(self.transport.clone());
+        self.projects
+            .insert(name.into(), Project { client, wallet_id });
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Project> {
+        self.projects.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> crate::Result<&Project> {
+        self.projects
+            .get(name)
+            .ok_or_else(|| ErrorMsg::UnknownProject(name.to_owned()).into())
+    }
+
+    /// Client for the named project
+    pub fn client(&self, name: &str) -> crate::Result<&ZebedeeClient> {
+        Ok(&self.get(name)?.client)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.projects.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.projects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.projects.is_empty()
+    }
+
+    /// Balance of a single project wallet in millisatoshis
+    pub async fn balance(&self, name: &str) -> crate::Result<u64> {
+        let wallet = self.client(name)?.get_wallet_details().await?;
+        wallet
+            .data
+            .ok_or_else(|| ErrorMsg::BadPayloadData(format!("no wallet data for {name}")))?
+            .balance_msats()
+    }
+
+    /// Fetches every project balance concurrently and sums them up
+    pub async fn balances(&self) -> crate::Result<ProjectBalances> {
+        let balances = try_join_all(self.names().map(|name| async move {
+            let balance = self.balance(name).await?;
+            crate::Result::<_>::Ok((name.to_owned(), balance))
+        }))
+        .await?;
+
+        let total = balances.iter().map(|(_, balance)| balance).sum();
+        Ok(ProjectBalances {
+            balances: balances.into_iter().collect(),
+            total,
+        })
+    }
+
+    /// Moves `amount` millisatoshis from one project wallet to another with an internal transfer
+    pub async fn transfer(
+        &self,
+        from: &str,
+        to: &str,
+        amount: u64,
+    ) -> crate::Result<InternalTransferResponse> {
+        let sender = self.client(from)?;
+        let receiver_wallet_id = self
+            .get(to)?
+            .wallet_id
+            .clone()
+            .ok_or_else(|| ErrorMsg::MissingWalletId(to.to_owned()))?;
+
+        let payload = InternalTransfer {
+            amount: amount.to_string(),
+            receiver_wallet_id,
+        };
+        sender.internal_transfer(&payload).await
+    }
+
+    /// Tops up `to` from `from` so that `to` holds at least `target_balance` millisatoshis.
+    /// Returns `None` when no transfer was needed.
+    pub async fn top_up(
+        &self,
+        from: &str,
+        to: &str,
+        target_balance: u64,
+    ) -> crate::Result<Option<InternalTransferResponse>> {
+        let balance = self.balance(to).await?;
+        match target_balance.checked_sub(balance) {
+            Some(missing) if missing > 0 => Ok(Some(self.transfer(from, to, missing).await?)),
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use super::*;
+use crate::charges::{ChargesData, InvoiceData};
+
+const URI: &str = "lightning:lnbc10n1pj9x7xmpp5s8eh5uzyk0j2u6mlvr7w0m7vq0x2ux3tsxhxtklp07ysfwdhw0qdq5g9kxy7fqd9h8vmmfvdjscqzzsxqyz5vqsp5k3r7dqzp2dqcfmq2z7ny3t4gh2g2q8jw6lmyhzq5j4e8v";
+
+#[test]
+fn test_qr_png() {
+    let options = QrOptions {
+        module_size: 2,
+        ..Default::default()
+    };
+    let png = InvoiceData {
+        request: String::new(),
+        uri: URI.to_owned(),
+    }
+    .to_qr_png(&options)
+    .unwrap();
+
+    assert!(png.starts_with(b"\x89PNG"));
+    let decoder = png::Decoder::new(png.as_slice());
+    let reader = decoder.read_info().unwrap();
+    let info = reader.info();
+    assert_eq!(info.width, info.height);
+    assert_eq!(info.width % 2, 0);
+}
+
+#[test]
+fn test_qr_svg_and_terminal() {
+    let options = QrOptions {
+        error_correction: QrErrorCorrection::High,
+        ..Default::default()
+    };
+    let svg = options.svg(URI).unwrap();
+    assert!(svg.contains("<svg"));
+
+    let terminal = QrOptions::default().terminal(URI).unwrap();
+    assert!(terminal.contains('▀') || terminal.contains('▄'));
+}
+
+#[test]
+fn test_qr_errors() {
+    // a charge without an invoice has nothing to encode
+    let charge: ChargesData = serde_json::from_str(
+        r#"{"id":"c6b1ca5b-0bc6-4e4d-a8e2-1bdd4d1f2a43","unit":"msats","amount":"1000",
+            "createdAt":null,"internalId":"","callbackUrl":"","description":"",
+            "expiresAt":null,"confirmedAt":null,"status":"pending","invoice":null}"#,
+    )
+    .unwrap();
+    assert!(charge.to_qr_svg(&QrOptions::default()).is_err());
+
+    let too_long = "a".repeat(8000);
+    assert!(QrOptions::default().png(&too_long).is_err());
+}
+
+#[test]
+fn test_qr_module_size_capped() {
+    let options = QrOptions {
+        module_size: u32::MAX,
+        quiet_zone: false,
+        ..Default::default()
+    };
+    let png = options.png("zbd").unwrap();
+    let reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+    // version 1 codes are 21 modules wide
+    assert_eq!(reader.info().width, 21 * MAX_MODULE_SIZE);
+}
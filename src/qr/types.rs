@@ -0,0 +1,170 @@
+use crate::{
+    charges::{ChargesData, InvoiceData},
+    errors::ErrorMsg,
+    withdrawal_request::{WithdrawInvoiceData, WithdrawalRequestsData},
+};
+use qrcode::{
+    render::{svg, unicode::Dense1x2},
+    Color, EcLevel, QrCode,
+};
+
+/// Share of the QR code that can be damaged and still be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QrErrorCorrection {
+    /// 7%
+    Low,
+    /// 15%
+    #[default]
+    Medium,
+    /// 25%
+    Quartile,
+    /// 30%
+    High,
+}
+
+impl From<QrErrorCorrection> for EcLevel {
+    fn from(value: QrErrorCorrection) -> Self {
+        match value {
+            QrErrorCorrection::Low => EcLevel::L,
+            QrErrorCorrection::Medium => EcLevel::M,
+            QrErrorCorrection::Quartile => EcLevel::Q,
+            QrErrorCorrection::High => EcLevel::H,
+        }
+    }
+}
+
+/// How a QR code is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QrOptions {
+    pub error_correction: QrErrorCorrection,
+    /// Width and height of a single module in pixels, 1 to 64, ignored by terminal output
+    pub module_size: u32,
+    /// Adds the 4 modules wide light border most scanners expect
+    pub quiet_zone: bool,
+}
+
+impl Default for QrOptions {
+    fn default() -> Self {
+        Self {
+            error_correction: QrErrorCorrection::default(),
+            module_size: 8,
+            quiet_zone: true,
+        }
+    }
+}
+
+const QUIET_ZONE: usize = 4;
+/// Largest module size, a version 40 code with its quiet zone is then 11 840 pixels wide
+pub const MAX_MODULE_SIZE: u32 = 64;
+
+impl QrOptions {
+    fn encode(&self, data: &str) -> crate::Result<QrCode> {
+        QrCode::with_error_correction_level(data, self.error_correction.into())
+            .map_err(|e| ErrorMsg::QrCode(e.to_string()).into())
+    }
+
+    fn module_size(&self) -> u32 {
+        self.module_size.clamp(1, MAX_MODULE_SIZE)
+    }
+
+    /// Renders `data` to an SVG document
+    pub fn svg(&self, data: &str) -> crate::Result<String> {
+        let size = self.module_size();
+        Ok(self
+            .encode(data)?
+            .render::<svg::Color>()
+            .module_dimensions(size, size)
+            .quiet_zone(self.quiet_zone)
+            .build())
+    }
+
+    /// Renders `data` to a grayscale PNG image
+    pub fn png(&self, data: &str) -> crate::Result<Vec<u8>> {
+        let code = self.encode(data)?;
+        let border = if self.quiet_zone { QUIET_ZONE } else { 0 };
+        let modules = code.width() + 2 * border;
+        let scale = self.module_size() as usize;
+        let too_large =
+            || ErrorMsg::QrCode(format!("{modules} modules at {scale} pixels is too large"));
+        let side = modules.checked_mul(scale).ok_or_else(too_large)?;
+        let side_u32 = u32::try_from(side).map_err(|_| too_large())?;
+        let area = side.checked_mul(side).ok_or_else(too_large)?;
+        let colors = code.to_colors();
+
+        let mut pixels = vec![u8::MAX; area];
+        for (i, color) in colors.iter().enumerate() {
+            if *color == Color::Light {
+                continue;
+            }
+            let (x, y) = (i % code.width() + border, i / code.width() + border);
+            for row in y * scale..(y + 1) * scale {
+                pixels[row * side + x * scale..row * side + (x + 1) * scale].fill(0);
+            }
+        }
+
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, side_u32, side_u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|e| ErrorMsg::QrCode(e.to_string()))?;
+        Ok(bytes)
+    }
+
+    /// Renders `data` with Unicode half blocks, two modules per character
+    pub fn terminal(&self, data: &str) -> crate::Result<String> {
+        Ok(self
+            .encode(data)?
+            .render::<Dense1x2>()
+            .quiet_zone(self.quiet_zone)
+            .build())
+    }
+}
+
+/// Anything that carries a payment URI worth showing as a QR code
+pub trait ToQrCode {
+    /// Text encoded in the QR code
+    fn qr_data(&self) -> crate::Result<&str>;
+
+    fn to_qr_svg(&self, options: &QrOptions) -> crate::Result<String> {
+        options.svg(self.qr_data()?)
+    }
+
+    fn to_qr_png(&self, options: &QrOptions) -> crate::Result<Vec<u8>> {
+        options.png(self.qr_data()?)
+    }
+
+    fn to_qr_terminal(&self, options: &QrOptions) -> crate::Result<String> {
+        options.terminal(self.qr_data()?)
+    }
+}
+
+impl ToQrCode for InvoiceData {
+    fn qr_data(&self) -> crate::Result<&str> {
+        Ok(&self.uri)
+    }
+}
+
+impl ToQrCode for ChargesData {
+    fn qr_data(&self) -> crate::Result<&str> {
+        self.invoice
+            .as_ref()
+            .ok_or_else(|| ErrorMsg::BadPayloadData(format!("no invoice for charge {}", self.id)))?
+            .qr_data()
+    }
+}
+
+/// Encodes `uri`, render `fast_uri` with [`QrOptions`] directly when it is preferred
+impl ToQrCode for WithdrawInvoiceData {
+    fn qr_data(&self) -> crate::Result<&str> {
+        Ok(&self.uri)
+    }
+}
+
+impl ToQrCode for WithdrawalRequestsData {
+    fn qr_data(&self) -> crate::Result<&str> {
+        self.invoice.qr_data()
+    }
+}
@@ -0,0 +1,105 @@
+use super::*;
+use crate::{
+    models::UnitType,
+    transaction::{Direction, Transaction},
+};
+
+fn tx(id: &str, internal_id: &str, amount: u64, status: &str) -> Transaction {
+    Transaction {
+        id: id.to_owned(),
+        direction: Direction::Outgoing,
+        amount: Some(amount),
+        fee: None,
+        unit: UnitType::Msats,
+        status: status.to_owned(),
+        created_at: None,
+        settled_at: None,
+        counterparty: None,
+        internal_id: Some(internal_id.to_owned()),
+    }
+}
+
+fn expected(
+    internal_id: &str,
+    kind: RecordKind,
+    amount: u64,
+    status: Option<&str>,
+) -> ExpectedRecord {
+    ExpectedRecord {
+        internal_id: internal_id.to_owned(),
+        kind,
+        amount_msats: amount,
+        status: status.map(str::to_owned),
+    }
+}
+
+#[tokio::test]
+async fn test_reconcile_records() {
+    let ours = vec![
+        expected("ok", RecordKind::Payment, 1000, Some("completed")),
+        expected("missing", RecordKind::Payment, 1000, None),
+        expected("dup", RecordKind::Payment, 1000, None),
+        expected("amount", RecordKind::Charge, 1000, None),
+        expected("status", RecordKind::Withdrawal, 1000, Some("completed")),
+        expected("twice", RecordKind::Payment, 1000, None),
+        expected("twice", RecordKind::Payment, 2000, None),
+    ];
+    let theirs = vec![
+        (RecordKind::Payment, tx("1", "ok", 1000, "Completed")),
+        (RecordKind::Payment, tx("2", "dup", 1000, "completed")),
+        (RecordKind::Payment, tx("3", "dup", 1000, "completed")),
+        (RecordKind::Charge, tx("4", "amount", 2000, "completed")),
+        (RecordKind::Withdrawal, tx("5", "status", 1000, "expired")),
+        (RecordKind::Charge, tx("6", "stranger", 1000, "completed")),
+        (RecordKind::Payment, tx("7", "", 1000, "completed")),
+        (RecordKind::Charge, tx("8", "ok", 1000, "completed")),
+        (RecordKind::Payment, tx("9", "twice", 1000, "completed")),
+    ];
+
+    let report = reconcile_records(&ours, &theirs);
+    assert_eq!(report.matched, 1);
+    assert!(!report.is_clean());
+
+    let kinds: Vec<_> = report
+        .discrepancies
+        .iter()
+        .map(|d| match d {
+            Discrepancy::Missing { expected } => format!("missing {}", expected.internal_id),
+            Discrepancy::DuplicateExpected { expected, actual } => {
+                format!("expected twice {} {}", expected.len(), actual[0].id)
+            }
+            Discrepancy::Duplicated { actual, .. } => format!("duplicated {}", actual.len()),
+            Discrepancy::AmountMismatch { actual, .. } => format!("amount {}", actual.id),
+            Discrepancy::StatusDiverged { actual, .. } => format!("status {}", actual.status),
+            Discrepancy::Unexpected { actual, .. } => format!("unexpected {}", actual.id),
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "missing missing",
+            "duplicated 2",
+            "amount 4",
+            "status expired",
+            "expected twice 2 9",
+            "unexpected 8",
+            "unexpected 6",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_balance_check() {
+    let check = BalanceCheck {
+        expected_msats: 5_000,
+        actual_msats: 3_000,
+    };
+    assert_eq!(check.difference(), -2_000);
+
+    let report = ReconciliationReport {
+        balance: Some(check),
+        ..Default::default()
+    };
+    assert!(!report.is_clean());
+    assert!(ReconciliationReport::default().is_clean());
+}
@@ -0,0 +1,178 @@
+use crate::transaction::Transaction;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Which list endpoint a record is reconciled against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    /// `get_charges`
+    Charge,
+    /// `get_payments`
+    Payment,
+    /// `get_withdrawal_requests`
+    Withdrawal,
+}
+
+/// What our own ledger believes happened, keyed by the `internal_id` sent to the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedRecord {
+    pub internal_id: String,
+    pub kind: RecordKind,
+    pub amount_msats: u64,
+    /// Status to compare against, skipped when `None`
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Discrepancy {
+    /// Expected record not found in the API
+    Missing { expected: ExpectedRecord },
+    /// More than one of our expected records carries the same kind and internal id.
+    /// They are not compared, the API entries for that id are listed in `actual`.
+    DuplicateExpected {
+        expected: Vec<ExpectedRecord>,
+        actual: Vec<Transaction>,
+    },
+    /// More than one API entry carries the same internal id
+    Duplicated {
+        expected: ExpectedRecord,
+        actual: Vec<Transaction>,
+    },
+    AmountMismatch {
+        expected: ExpectedRecord,
+        actual: Transaction,
+    },
+    StatusDiverged {
+        expected: ExpectedRecord,
+        actual: Transaction,
+    },
+    /// API entry with an internal id our ledger does not know about
+    Unexpected {
+        kind: RecordKind,
+        actual: Transaction,
+    },
+}
+
+/// Wallet balance according to our ledger compared with `get_wallet_details`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceCheck {
+    pub expected_msats: u64,
+    pub actual_msats: u64,
+}
+
+impl BalanceCheck {
+    /// Actual minus expected balance in millisatoshis
+    pub fn difference(&self) -> i128 {
+        self.actual_msats as i128 - self.expected_msats as i128
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.expected_msats == self.actual_msats
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    /// Number of expected records that matched exactly one API entry
+    pub matched: usize,
+    pub discrepancies: Vec<Discrepancy>,
+    pub balance: Option<BalanceCheck>,
+}
+
+impl ReconciliationReport {
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty() && self.balance.map(|b| b.is_balanced()).unwrap_or(true)
+    }
+}
+
+/// Compares our expected records with the entries returned by the API.
+/// API entries without an internal id are ignored.
+pub fn reconcile_records(
+    expected: &[ExpectedRecord],
+    actual: &[(RecordKind, Transaction)],
+) -> ReconciliationReport {
+    let mut by_id: HashMap<(RecordKind, &str), Vec<&Transaction>> = HashMap::new();
+    for (kind, tx) in actual {
+        if let Some(internal_id) = tx.internal_id.as_deref().filter(|id| !id.is_empty()) {
+            by_id.entry((*kind, internal_id)).or_default().push(tx);
+        }
+    }
+
+    let mut expected_by_id: HashMap<(RecordKind, &str), Vec<&ExpectedRecord>> = HashMap::new();
+    for record in expected {
+        expected_by_id
+            .entry((record.kind, record.internal_id.as_str()))
+            .or_default()
+            .push(record);
+    }
+
+    let mut report = ReconciliationReport::default();
+    for record in expected {
+        let key = (record.kind, record.internal_id.as_str());
+        // every key is handled at its first record
+        let Some(records) = expected_by_id.remove(&key) else {
+            continue;
+        };
+        if records.len() > 1 {
+            report.discrepancies.push(Discrepancy::DuplicateExpected {
+                expected: records.into_iter().cloned().collect(),
+                actual: by_id
+                    .remove(&key)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+            });
+            continue;
+        }
+
+        let found = by_id
+            .remove(&(record.kind, record.internal_id.as_str()))
+            .unwrap_or_default();
+
+        let discrepancy = match found.as_slice() {
+            [] => Some(Discrepancy::Missing {
+                expected: record.clone(),
+            }),
+            [tx] if tx.amount_msats() != Some(record.amount_msats) => {
+                Some(Discrepancy::AmountMismatch {
+                    expected: record.clone(),
+                    actual: (*tx).clone(),
+                })
+            }
+            [tx] => match &record.status {
+                Some(status) if !status.eq_ignore_ascii_case(&tx.status) => {
+                    Some(Discrepancy::StatusDiverged {
+                        expected: record.clone(),
+                        actual: (*tx).clone(),
+                    })
+                }
+                _ => None,
+            },
+            _ => Some(Discrepancy::Duplicated {
+                expected: record.clone(),
+                actual: found.iter().map(|tx| (*tx).clone()).collect(),
+            }),
+        };
+
+        match discrepancy {
+            Some(discrepancy) => report.discrepancies.push(discrepancy),
+            None => report.matched += 1,
+        }
+    }
+
+    let mut unexpected: Vec<_> = by_id.into_iter().collect();
+    unexpected.sort_by(|a, b| a.0.cmp(&b.0));
+    for ((kind, _), txs) in unexpected {
+        for tx in txs {
+            report.discrepancies.push(Discrepancy::Unexpected {
+                kind,
+                actual: tx.clone(),
+            });
+        }
+    }
+
+    report
+}
@@ -0,0 +1,91 @@
+use super::*;
+use crate::{
+    config::ZebedeeConfig, login_with_zbd::FetchTokenBody, transport::MockTransport, ZebedeeClient,
+    PKCE,
+};
+
+#[test]
+fn test_secret_is_redacted() {
+    let secret = Secret::new("my-api-key");
+    assert_eq!(secret.expose_secret(), "my-api-key");
+    assert_eq!(format!("{secret:?}"), "[REDACTED]");
+    assert_eq!(secret.to_string(), "[REDACTED]");
+
+    let json = serde_json::to_string(&secret).unwrap();
+    assert_eq!(json, r#""my-api-key""#);
+    let back: Secret = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.expose_secret(), "my-api-key");
+}
+
+#[test]
+fn test_credentials_do_not_leak_through_debug() {
+    let client_secret = "c3b5d2a1-0000-4000-8000-0000000000aa";
+    let client = ZebedeeClient::new("my-api-key").oauth(
+        "c3b5d2a1-0000-4000-8000-000000000001".to_owned(),
+        client_secret.to_owned(),
+        "http://localhost/callback".to_owned(),
+        "c3b5d2a1-0000-4000-8000-000000000002".to_owned(),
+        "user".to_owned(),
+    );
+    let printed = format!("{client:?}");
+    assert!(!printed.contains("my-api-key"), "{printed}");
+    assert!(!printed.contains(client_secret), "{printed}");
+
+    let pkce = PKCE::new_rand();
+    let body = FetchTokenBody::new(&client, "some-code", pkce.verifier.expose_secret());
+    let printed = format!("{body:?} {pkce:?}");
+    assert!(!printed.contains(client_secret), "{printed}");
+    assert!(!printed.contains("some-code"), "{printed}");
+    assert!(
+        !printed.contains(pkce.verifier.expose_secret()),
+        "{printed}"
+    );
+
+    // the request body still carries the real values
+    let json = serde_json::to_value(&body).unwrap();
+    assert_eq!(json["client_secret"], client_secret);
+    assert_eq!(json["code_verifier"], pkce.verifier.expose_secret());
+
+    let config: ZebedeeConfig = "[profiles.default]\napikey = \"profile-key\""
+        .parse()
+        .unwrap();
+    let printed = format!("{config:?}");
+    assert!(!printed.contains("profile-key"), "{printed}");
+}
+
+#[tokio::test]
+async fn test_sent_requests_do_not_leak_through_debug() {
+    let client_secret = "c3b5d2a1-0000-4000-8000-0000000000bb";
+    let code = "c3b5d2a1-0000-4000-8000-0000000000cc";
+    let transport = MockTransport::new();
+    transport.respond_always(500, r#"{"success":false}"#);
+    let client = ZebedeeClient::new("my-api-key")
+        .oauth(
+            "c3b5d2a1-0000-4000-8000-000000000001".to_owned(),
+            client_secret.to_owned(),
+            "http://localhost/callback".to_owned(),
+            "c3b5d2a1-0000-4000-8000-000000000002".to_owned(),
+            "user".to_owned(),
+        )
+        .transport(transport.clone());
+
+    let pkce = PKCE::new_rand();
+    let _ = client.get_wallet_details().await;
+    let _ = client
+        .fetch_token(code, pkce.verifier.expose_secret())
+        .await;
+    let _ = client.fetch_user_data("some-access-token").await;
+    assert_eq!(transport.requests().len(), 3);
+
+    let printed = format!("{client:?} {:?}", transport.requests());
+    for secret in [
+        "my-api-key",
+        client_secret,
+        code,
+        pkce.verifier.expose_secret(),
+        "some-access-token",
+    ] {
+        assert!(!printed.contains(secret), "{secret} in {printed}");
+    }
+    assert!(printed.contains("[REDACTED]"), "{printed}");
+}
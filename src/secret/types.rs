@@ -0,0 +1,89 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    borrow::Cow,
+    fmt::{Debug, Display},
+};
+use validator::{validate_length, ValidationError};
+use zeroize::Zeroize;
+
+/// Credential such as an apikey, OAuth secret, token or PKCE verifier.
+///
+/// Prints as `[REDACTED]` and is zeroed when dropped. Serializes to the real value,
+/// so request bodies carrying it reach the API unchanged.
+#[derive(Clone, Default)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    /// The wrapped value, for the places that have to send it
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl Display for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+}
+
+impl From<Cow<'_, str>> for Secret {
+    fn from(value: Cow<'_, str>) -> Self {
+        Self(value.into_owned())
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+fn exact_length(secret: &Secret, length: u64) -> Result<(), ValidationError> {
+    match validate_length(secret.expose_secret(), None, None, Some(length)) {
+        true => Ok(()),
+        false => Err(ValidationError::new("length")),
+    }
+}
+
+/// `#[validate(custom = "...")]` stand-in for `length(equal = 36)`, which only accepts strings
+pub(crate) fn length_36(secret: &Secret) -> Result<(), ValidationError> {
+    exact_length(secret, 36)
+}
@@ -0,0 +1,134 @@
+use super::*;
+use crate::{
+    errors::*, gamertag::GamertagPayment, payments::Payment, transport::MockTransport,
+    ZebedeeClient,
+};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_per_transaction_and_budget_limits() {
+    let policy = SpendPolicy::new()
+        .max_per_transaction(5_000)
+        .daily_budget(8_000);
+
+    assert!(policy.reserve("alice@zbd.gg", 5_000).await.is_ok());
+
+    let err = policy.reserve("bob@zbd.gg", 6_000).await.unwrap_err();
+    match err {
+        ZebedeeError::Msg(ErrorMsg::LimitExceeded(violation)) => {
+            assert_eq!(violation.limit, SpendLimit::PerTransaction { max: 5_000 });
+        }
+        e => panic!("unexpected error {e}"),
+    }
+
+    let err = policy.reserve("bob@zbd.gg", 4_000).await.unwrap_err();
+    match err {
+        ZebedeeError::Msg(ErrorMsg::LimitExceeded(violation)) => {
+            assert_eq!(violation.remaining, 3_000);
+        }
+        e => panic!("unexpected error {e}"),
+    }
+
+    assert!(policy.reserve("bob@zbd.gg", 3_000).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_per_recipient_limit_and_release() {
+    let policy = SpendPolicy::new().per_recipient(Duration::from_secs(3600), 2_000);
+
+    let record = policy.reserve("carol@zbd.gg", 2_000).await.unwrap();
+    assert!(policy.reserve("carol@zbd.gg", 1).await.is_err());
+    assert!(policy.reserve("dave@zbd.gg", 2_000).await.is_ok());
+
+    policy.release(&record).await.unwrap();
+    assert!(policy.reserve("carol@zbd.gg", 1_000).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_client_rejects_before_sending() {
+    let zebedee_client = ZebedeeClient::new("apikey")
+        .transport(MockTransport::new())
+        .spend_policy(SpendPolicy::new().max_per_transaction(1_000));
+
+    let payment = GamertagPayment {
+        gamertag: String::from("miketwenty1"),
+        amount: String::from("5000"),
+        ..Default::default()
+    };
+    let err = zebedee_client.pay_gamertag(&payment).await.unwrap_err();
+    assert!(err.to_string().contains("Spend limit exceeded"));
+
+    let payment = Payment {
+        invoice: String::from("lnbc1pjqqqqqpp5"),
+        ..Default::default()
+    };
+    let err = zebedee_client.pay_invoice(&payment).await.unwrap_err();
+    assert!(err.to_string().contains("unknown amount"));
+}
+
+#[tokio::test]
+async fn test_recipient_limit_shared_across_entry_points() {
+    use crate::{email::EmailPaymentReqest, ln_address::LnPayment};
+
+    assert_eq!(
+        recipient_key(&"@Alice".parse().unwrap()),
+        recipient_key(&"alice@ZBD.gg".parse().unwrap())
+    );
+    assert_eq!(
+        recipient_key(&"mailto:Bob@example.com".parse().unwrap()),
+        recipient_key(&"bob@example.com".parse().unwrap())
+    );
+
+    let zebedee_client = ZebedeeClient::new("apikey")
+        .transport(MockTransport::new())
+        .spend_policy(SpendPolicy::new().per_recipient(Duration::from_secs(3600), 1_000));
+
+    // the transport fails after the budget is reserved, so the reservation stays
+    let payment = GamertagPayment {
+        gamertag: String::from("Alice"),
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+    assert!(zebedee_client.pay_gamertag(&payment).await.is_err());
+
+    let payment = LnPayment {
+        ln_address: "alice@zbd.gg".parse().unwrap(),
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+    let err = zebedee_client.pay_ln_address(&payment).await.unwrap_err();
+    assert!(err.to_string().contains("Spend limit exceeded"), "{err}");
+
+    let payment = EmailPaymentReqest {
+        email: String::from("ALICE@zbd.gg"),
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+    let err = zebedee_client.pay_email(&payment).await.unwrap_err();
+    assert!(err.to_string().contains("Spend limit exceeded"), "{err}");
+}
+
+#[tokio::test]
+async fn test_reservation_kept_unless_the_api_refused() {
+    let transport = MockTransport::new();
+    transport.respond(400, r#"{"success":false,"message":"Bad gamertag"}"#);
+    transport.respond(502, r#"{"success":false,"message":"Bad gateway"}"#);
+    let zebedee_client = ZebedeeClient::new("apikey")
+        .transport(transport.clone())
+        .spend_policy(SpendPolicy::new().per_recipient(Duration::from_secs(3600), 1_000));
+    let payment = GamertagPayment {
+        gamertag: String::from("bob"),
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+
+    // a 4xx never paid, the budget is given back
+    let err = zebedee_client.pay_gamertag(&payment).await.unwrap_err();
+    assert_eq!(err.to_string(), "Bad gamertag");
+    // a 5xx may have paid, the budget stays spent
+    let err = zebedee_client.pay_gamertag(&payment).await.unwrap_err();
+    assert_eq!(err.to_string(), "Bad gateway");
+    let err = zebedee_client.pay_gamertag(&payment).await.unwrap_err();
+    assert!(err.to_string().contains("Spend limit exceeded"), "{err}");
+    assert_eq!(transport.requests().len(), 2);
+}
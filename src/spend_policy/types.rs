@@ -0,0 +1,308 @@
+use crate::{
+    destination::PaymentDestination,
+    errors::{ErrorMsg, ZebedeeError},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::{
+    fmt::Display,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// A payout that was let through by a [`SpendPolicy`]. Amounts are in millisatoshis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendRecord {
+    pub recipient: String,
+    pub amount: u64,
+    pub at: DateTime<Utc>,
+}
+
+/// Key a payout to `destination` is recorded under, used by every payment method so
+/// per-recipient limits hold whichever entry point pays. A ZBD Gamertag shares its key with
+/// its `@zbd.gg` Lightning Address, and an email with the Lightning Address spelled the same.
+pub fn recipient_key(destination: &PaymentDestination) -> String {
+    match destination {
+        PaymentDestination::Gamertag(gamertag) => {
+            format!(
+                "{}@zbd.gg",
+                gamertag.trim().trim_start_matches('@').to_lowercase()
+            )
+        }
+        PaymentDestination::LnAddress(value)
+        | PaymentDestination::Email(value)
+        | PaymentDestination::Invoice(value)
+        | PaymentDestination::Lnurl(value)
+        | PaymentDestination::NodePubkey(value) => value.trim().to_lowercase(),
+    }
+}
+
+/// Storage for the spend history a [`SpendPolicy`] checks its budgets against
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait SpendStore: Send + Sync {
+    /// All records made at or after `since`
+    async fn records_since(&self, since: DateTime<Utc>) -> crate::Result<Vec<SpendRecord>>;
+    async fn insert(&self, record: SpendRecord) -> crate::Result<()>;
+    async fn remove(&self, record: &SpendRecord) -> crate::Result<()>;
+    /// Drops records made before `before`, they no longer count against any budget
+    async fn prune(&self, _before: DateTime<Utc>) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+/// In-process [`SpendStore`], history is lost on restart
+#[derive(Debug, Default)]
+pub struct MemorySpendStore {
+    records: Mutex<Vec<SpendRecord>>,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl SpendStore for MemorySpendStore {
+    async fn records_since(&self, since: DateTime<Utc>) -> crate::Result<Vec<SpendRecord>> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(records.iter().filter(|r| r.at >= since).cloned().collect())
+    }
+
+    async fn insert(&self, record: SpendRecord) -> crate::Result<()> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.push(record);
+        Ok(())
+    }
+
+    async fn remove(&self, record: &SpendRecord) -> crate::Result<()> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(i) = records.iter().position(|r| r == record) {
+            records.remove(i);
+        }
+        Ok(())
+    }
+
+    async fn prune(&self, before: DateTime<Utc>) -> crate::Result<()> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.retain(|r| r.at >= before);
+        Ok(())
+    }
+}
+
+/// A single rule enforced by a [`SpendPolicy`]. Amounts are in millisatoshis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpendLimit {
+    PerTransaction {
+        max: u64,
+    },
+    /// Rolling budget across every recipient
+    Budget {
+        window: Duration,
+        max: u64,
+    },
+    /// Rolling budget for each recipient on its own
+    PerRecipient {
+        window: Duration,
+        max: u64,
+    },
+}
+
+impl Display for SpendLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpendLimit::PerTransaction { max } => write!(f, "max {max} msats per transaction"),
+            SpendLimit::Budget { window, max } => {
+                write!(f, "max {max} msats per {}s", window.as_secs())
+            }
+            SpendLimit::PerRecipient { window, max } => {
+                write!(f, "max {max} msats per recipient per {}s", window.as_secs())
+            }
+        }
+    }
+}
+
+/// Details of a payout rejected by a [`SpendPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendViolation {
+    pub limit: SpendLimit,
+    pub recipient: String,
+    pub attempted: u64,
+    pub remaining: u64,
+}
+
+impl Display for SpendViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} msats to {} exceeds {} ({} msats remaining)",
+            self.attempted, self.recipient, self.limit, self.remaining
+        )
+    }
+}
+
+/// Payout limits checked by `ZebedeeClient` before a payment request is sent
+pub struct SpendPolicy {
+    limits: Vec<SpendLimit>,
+    store: Arc<dyn SpendStore>,
+    lock: tokio::sync::Mutex<()>,
+}
+
+impl Default for SpendPolicy {
+    fn default() -> Self {
+        Self {
+            limits: Vec::new(),
+            store: Arc::new(MemorySpendStore::default()),
+            lock: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+impl std::fmt::Debug for SpendPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpendPolicy")
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SpendPolicy {
+    /// Policy without limits backed by a [`MemorySpendStore`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn store<S: SpendStore + 'static>(self, store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            ..self
+        }
+    }
+
+    pub fn limit(mut self, limit: SpendLimit) -> Self {
+        self.limits.push(limit);
+        self
+    }
+
+    pub fn max_per_transaction(self, max: u64) -> Self {
+        self.limit(SpendLimit::PerTransaction { max })
+    }
+
+    pub fn hourly_budget(self, max: u64) -> Self {
+        self.budget(Duration::from_secs(60 * 60), max)
+    }
+
+    pub fn daily_budget(self, max: u64) -> Self {
+        self.budget(Duration::from_secs(24 * 60 * 60), max)
+    }
+
+    pub fn budget(self, window: Duration, max: u64) -> Self {
+        self.limit(SpendLimit::Budget { window, max })
+    }
+
+    pub fn per_recipient(self, window: Duration, max: u64) -> Self {
+        self.limit(SpendLimit::PerRecipient { window, max })
+    }
+
+    pub fn limits(&self) -> &[SpendLimit] {
+        &self.limits
+    }
+
+    /// Checks `amount` against every limit and records it when allowed.
+    /// `recipient` should come from [`recipient_key`].
+    /// Checks and records are serialized so concurrent payouts cannot overdraw a budget.
+    pub async fn reserve(&self, recipient: &str, amount: u64) -> crate::Result<SpendRecord> {
+        let _guard = self.lock.lock().await;
+        let now = Utc::now();
+
+        let longest = self
+            .limits
+            .iter()
+            .filter_map(|limit| match limit {
+                SpendLimit::PerTransaction { .. } => None,
+                SpendLimit::Budget { window, .. } | SpendLimit::PerRecipient { window, .. } => {
+                    Some(*window)
+                }
+            })
+            .max();
+
+        let history = match longest {
+            Some(window) => {
+                let since = window_start(now, window);
+                self.store.prune(since).await?;
+                self.store.records_since(since).await?
+            }
+            None => Vec::new(),
+        };
+
+        for limit in &self.limits {
+            let (max, spent) = match limit {
+                SpendLimit::PerTransaction { max } => (*max, 0),
+                SpendLimit::Budget { window, max } => {
+                    let since = window_start(now, *window);
+                    let spent = history.iter().filter(|r| r.at >= since).map(|r| r.amount);
+                    (*max, spent.sum())
+                }
+                SpendLimit::PerRecipient { window, max } => {
+                    let since = window_start(now, *window);
+                    let spent = history
+                        .iter()
+                        .filter(|r| r.at >= since && r.recipient == recipient)
+                        .map(|r| r.amount);
+                    (*max, spent.sum())
+                }
+            };
+
+            let remaining = max.saturating_sub(spent);
+            if amount > remaining {
+                return Err(ErrorMsg::LimitExceeded(SpendViolation {
+                    limit: limit.clone(),
+                    recipient: recipient.to_owned(),
+                    attempted: amount,
+                    remaining,
+                })
+                .into());
+            }
+        }
+
+        let record = SpendRecord {
+            recipient: recipient.to_owned(),
+            amount,
+            at: now,
+        };
+        self.store.insert(record.clone()).await?;
+        Ok(record)
+    }
+
+    /// Gives back a reservation for a payout that was never sent
+    pub async fn release(&self, record: &SpendRecord) -> crate::Result<()> {
+        let _guard = self.lock.lock().await;
+        self.store.remove(record).await
+    }
+
+    /// Runs `payout` under the policy. The reservation is released when local checks or a 4xx
+    /// answer reject the payout, but kept on any other error since the payment may have gone
+    /// through.
+    pub(crate) async fn guard<T, F>(
+        &self,
+        recipient: &str,
+        amount: u64,
+        payout: F,
+    ) -> crate::Result<T>
+    where
+        F: std::future::Future<Output = crate::Result<T>>,
+    {
+        let record = self.reserve(recipient, amount).await?;
+        let result = payout.await;
+        if result
+            .as_ref()
+            .is_err_and(ZebedeeError::is_definitely_not_sent)
+        {
+            self.release(&record).await?;
+        }
+        result
+    }
+}
+
+fn window_start(now: DateTime<Utc>, window: Duration) -> DateTime<Utc> {
+    chrono::Duration::from_std(window)
+        .ok()
+        .and_then(|window| now.checked_sub_signed(window))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
@@ -0,0 +1,76 @@
+use super::*;
+use crate::{
+    email::EmailPaymentRes, internal_transfer::InternalTransferData, models::UnitType,
+    payments::PaymentsData,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_transaction_from_payment() {
+    let payment: PaymentsData = serde_json::from_value(json!({
+        "id": "5d88b2e0-e491-40e1-a8a8-a81ae68f2297",
+        "fee": "2000",
+        "unit": "msats",
+        "amount": "120000",
+        "invoice": "lnbc1200n1p0",
+        "preimage": null,
+        "internalId": "order-11",
+        "processedAt": "2023-05-01T10:00:00.000Z",
+        "confirmedAt": "2023-05-01T10:00:01.000Z",
+        "description": "prize",
+        "status": "completed"
+    }))
+    .unwrap();
+
+    let tx = Transaction::from(&payment);
+    assert_eq!(tx.direction, Direction::Outgoing);
+    assert_eq!(tx.amount_msats(), Some(120_000));
+    assert_eq!(tx.fee_msats(), Some(2_000));
+    assert_eq!(tx.status, "completed");
+    assert_eq!(tx.internal_id.as_deref(), Some("order-11"));
+    assert!(tx.settled_at.is_some());
+}
+
+#[tokio::test]
+async fn test_transaction_from_voucher_and_transfer() {
+    let res: EmailPaymentRes = serde_json::from_value(json!({
+        "amount": "15",
+        "code": "ZBDVOUCHER",
+        "createdAt": "2023-05-01T10:00:00.000Z",
+        "createTransactionId": "9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a01",
+        "description": "gift",
+        "fee": "1",
+        "id": "9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a02",
+        "unit": "sats",
+        "walletId": "9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a03"
+    }))
+    .unwrap();
+
+    let tx: Transaction = res.into();
+    assert_eq!(tx.id, "9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a02");
+    assert_eq!(tx.unit, UnitType::Sats);
+    assert_eq!(tx.amount_msats(), Some(15_000));
+    assert_eq!(tx.fee_msats(), Some(1_000));
+
+    let transfer: InternalTransferData = serde_json::from_value(json!({
+        "id": "transfer-1",
+        "status": "completed",
+        "amount": "not a number",
+        "senderWalletId": "9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a03",
+        "receiverWalletId": "9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a04",
+        "userId": "9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a05",
+        "sendTxId": "9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a06",
+        "receiveTxId": "9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a07",
+        "createdAt": null,
+        "updatedAt": null
+    }))
+    .unwrap();
+
+    let tx: Transaction = transfer.into();
+    assert_eq!(tx.direction, Direction::Internal);
+    assert_eq!(tx.amount, None);
+    assert_eq!(
+        tx.counterparty.as_deref(),
+        Some("9b2b6a4e-6f0a-4d49-8a0e-1b1b7f1c0a04")
+    );
+}
@@ -0,0 +1,282 @@
+use crate::{
+    charges::*, destination::PaymentOutcome, email::*, gamertag::*, internal_transfer::*,
+    keysend::*, ln_address::*, models::UnitType, payments::*, voucher::*, withdrawal_request::*,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+    /// Transfer between two Project Wallets you own
+    Internal,
+}
+
+/// Common view over every kind of money movement returned by the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: String,
+    pub direction: Direction,
+    /// Amount in `unit`, `None` when the API value could not be read
+    pub amount: Option<u64>,
+    /// Fee in `unit`, `None` when unknown
+    pub fee: Option<u64>,
+    pub unit: UnitType,
+    pub status: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub settled_at: Option<DateTime<Utc>>,
+    /// Receiver id, wallet id or invoice depending on the payment type
+    pub counterparty: Option<String>,
+    pub internal_id: Option<String>,
+}
+
+impl Transaction {
+    pub fn amount_msats(&self) -> Option<u64> {
+        self.amount.map(|amount| self.unit.to_msats(amount))
+    }
+
+    pub fn fee_msats(&self) -> Option<u64> {
+        self.fee.map(|fee| self.unit.to_msats(fee))
+    }
+}
+
+fn amount(value: &str) -> Option<u64> {
+    value.parse().ok()
+}
+
+fn unit(value: &str) -> UnitType {
+    value.parse().unwrap_or_default()
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+macro_rules! impl_from_owned {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Transaction {
+            fn from(value: $t) -> Self {
+                Transaction::from(&value)
+            }
+        })*
+    };
+}
+
+impl_from_owned!(
+    PaymentsData,
+    GamertagPaymentData,
+    GamertagTxData,
+    LnSendPaymentData,
+    KeysendTx,
+    KeysendData,
+    EmailPaymentData,
+    EmailPaymentRes,
+    InternalTransferData,
+    VoucherData,
+    PaymentOutcome,
+    ChargesData,
+    WithdrawalRequestsData
+);
+
+impl From<&PaymentsData> for Transaction {
+    fn from(value: &PaymentsData) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Outgoing,
+            amount: amount(&value.amount),
+            fee: value.fee.as_deref().and_then(amount),
+            unit: unit(&value.unit),
+            status: value.status.clone().unwrap_or_default(),
+            created_at: value.processed_at,
+            settled_at: value.confirmed_at,
+            counterparty: value.invoice.clone(),
+            internal_id: value.internal_id.clone(),
+        }
+    }
+}
+
+impl From<&GamertagPaymentData> for Transaction {
+    fn from(value: &GamertagPaymentData) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Outgoing,
+            amount: amount(&value.amount),
+            fee: None,
+            unit: UnitType::Msats,
+            status: value.status.clone(),
+            created_at: None,
+            settled_at: Some(value.settled_at),
+            counterparty: Some(value.receiver_id.to_string()),
+            internal_id: None,
+        }
+    }
+}
+
+impl From<&GamertagTxData> for Transaction {
+    fn from(value: &GamertagTxData) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Outgoing,
+            amount: amount(&value.amount),
+            fee: amount(&value.fee),
+            unit: unit(&value.unit),
+            status: value.status.clone(),
+            created_at: value.processed_at,
+            settled_at: value.confirmed_at,
+            counterparty: Some(value.receiver_id.to_string()),
+            internal_id: None,
+        }
+    }
+}
+
+impl From<&LnSendPaymentData> for Transaction {
+    fn from(value: &LnSendPaymentData) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Outgoing,
+            amount: amount(&value.amount),
+            fee: value.fee.as_deref().and_then(amount),
+            unit: unit(&value.unit),
+            status: value.status.clone(),
+            created_at: Some(value.created_at),
+            settled_at: Some(value.processed_at),
+            counterparty: non_empty(&value.invoice),
+            internal_id: value.internal_id.clone(),
+        }
+    }
+}
+
+impl From<&KeysendTx> for Transaction {
+    fn from(value: &KeysendTx) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Outgoing,
+            amount: amount(&value.amount),
+            fee: amount(&value.fee),
+            unit: UnitType::Msats,
+            status: value.status.clone(),
+            created_at: None,
+            settled_at: value.confirmed_at,
+            counterparty: None,
+            internal_id: None,
+        }
+    }
+}
+
+impl From<&KeysendData> for Transaction {
+    fn from(value: &KeysendData) -> Self {
+        Transaction::from(&value.transaction)
+    }
+}
+
+impl From<&EmailPaymentData> for Transaction {
+    fn from(value: &EmailPaymentData) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Outgoing,
+            amount: Some(value.amount),
+            fee: None,
+            unit: UnitType::Msats,
+            status: value.status.clone(),
+            created_at: None,
+            settled_at: Some(value.settled_at),
+            counterparty: Some(value.receiver_id.to_string()),
+            internal_id: None,
+        }
+    }
+}
+
+impl From<&EmailPaymentRes> for Transaction {
+    fn from(value: &EmailPaymentRes) -> Self {
+        match value {
+            EmailPaymentRes::ExistingZbdAccount(data) => data.into(),
+            EmailPaymentRes::Voucher(data) => data.into(),
+        }
+    }
+}
+
+impl From<&InternalTransferData> for Transaction {
+    fn from(value: &InternalTransferData) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Internal,
+            amount: amount(&value.amount),
+            fee: None,
+            unit: UnitType::Msats,
+            status: value.status.clone(),
+            created_at: value.created_at,
+            settled_at: value.updated_at,
+            counterparty: Some(value.receiver_wallet_id.to_string()),
+            internal_id: None,
+        }
+    }
+}
+
+impl From<&VoucherData> for Transaction {
+    fn from(value: &VoucherData) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Outgoing,
+            amount: Some(value.amount),
+            fee: value.fee,
+            unit: value.unit,
+            // vouchers carry no status, they exist once created
+            status: String::from("created"),
+            created_at: Some(value.created_at),
+            settled_at: None,
+            counterparty: None,
+            internal_id: None,
+        }
+    }
+}
+
+impl From<&PaymentOutcome> for Transaction {
+    fn from(value: &PaymentOutcome) -> Self {
+        match value {
+            PaymentOutcome::Invoice(data) => data.into(),
+            PaymentOutcome::LnAddress(data) => data.into(),
+            PaymentOutcome::Gamertag(data) => data.into(),
+            PaymentOutcome::Email(data) => data.into(),
+            PaymentOutcome::Keysend(data) => data.into(),
+        }
+    }
+}
+
+impl From<&ChargesData> for Transaction {
+    fn from(value: &ChargesData) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Incoming,
+            amount: amount(&value.amount),
+            fee: None,
+            unit: unit(&value.unit),
+            status: value.status.clone(),
+            created_at: value.created_at,
+            settled_at: value.confirmed_at,
+            counterparty: value
+                .invoice
+                .as_ref()
+                .map(|invoice| invoice.request.clone()),
+            internal_id: non_empty(&value.internal_id),
+        }
+    }
+}
+
+impl From<&WithdrawalRequestsData> for Transaction {
+    fn from(value: &WithdrawalRequestsData) -> Self {
+        Transaction {
+            id: value.id.to_string(),
+            direction: Direction::Outgoing,
+            amount: amount(&value.amount),
+            fee: None,
+            unit: unit(&value.unit),
+            status: value.status.clone(),
+            created_at: Some(value.created_at),
+            settled_at: None,
+            counterparty: None,
+            internal_id: non_empty(&value.internal_id),
+        }
+    }
+}
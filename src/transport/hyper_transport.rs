@@ -0,0 +1,64 @@
+use crate::{
+    errors::ZebedeeError,
+    transport::{HttpRequest, HttpResponse, HttpTransport},
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper_util::client::legacy::{connect::Connect, Client};
+use std::fmt::Debug;
+
+/// [`HttpTransport`] over a hyper client, the connector decides the TLS stack.
+///
+/// ```ignore
+/// let connector = hyper_rustls::HttpsConnectorBuilder::new()
+///     .with_webpki_roots()
+///     .https_only()
+///     .enable_http1()
+///     .build();
+/// let client = Client::builder(TokioExecutor::new()).build(connector);
+/// let zebedee_client = ZebedeeClient::new(apikey).transport(HyperTransport::new(client));
+/// ```
+#[derive(Clone)]
+pub struct HyperTransport<C> {
+    client: Client<C, Full<Bytes>>,
+}
+
+impl<C> HyperTransport<C> {
+    pub fn new(client: Client<C, Full<Bytes>>) -> Self {
+        Self { client }
+    }
+}
+
+impl<C> Debug for HyperTransport<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HyperTransport")
+    }
+}
+
+#[async_trait]
+impl<C> HttpTransport for HyperTransport<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    async fn send(&self, request: HttpRequest) -> crate::Result<HttpResponse> {
+        let request = request
+            .into_http()?
+            .map(|body| Full::new(Bytes::from(body)));
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|e| ZebedeeError::Transport(e.into()))?;
+
+        let status = response.status().as_u16();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(|e| ZebedeeError::Transport(e.into()))?
+            .to_bytes()
+            .to_vec();
+        Ok(HttpResponse { status, body })
+    }
+}
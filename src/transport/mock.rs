@@ -0,0 +1,83 @@
+use crate::{
+    errors::ZebedeeError,
+    transport::{HttpRequest, HttpResponse, HttpTransport},
+};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+#[derive(Debug, Default)]
+struct MockState {
+    responses: VecDeque<HttpResponse>,
+    fallback: Option<HttpResponse>,
+    requests: Vec<HttpRequest>,
+}
+
+/// [`HttpTransport`] answering with canned responses, for tests without a socket.
+///
+/// Clones share their responses and recorded requests, keep one to inspect what was sent.
+/// Queued responses are used first, in order, then the one set with `respond_always`.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Answers the next request with `status` and `body`
+    pub fn respond(&self, status: u16, body: impl Into<Vec<u8>>) {
+        self.state()
+            .responses
+            .push_back(HttpResponse::new(status, body));
+    }
+
+    /// Answers the next request with `status` and `body` as JSON
+    pub fn respond_json<T: Serialize>(&self, status: u16, body: &T) -> crate::Result<()> {
+        self.respond(status, serde_json::to_vec(body)?);
+        Ok(())
+    }
+
+    /// Answers every request once the queued responses are used up
+    pub fn respond_always(&self, status: u16, body: impl Into<Vec<u8>>) {
+        self.state().fallback = Some(HttpResponse::new(status, body));
+    }
+
+    /// Every request received so far, oldest first
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.state().requests.clone()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpTransport for MockTransport {
+    async fn send(&self, request: HttpRequest) -> crate::Result<HttpResponse> {
+        let mut state = self.state();
+        let response = state
+            .responses
+            .pop_front()
+            .or_else(|| state.fallback.clone())
+            .ok_or_else(|| {
+                ZebedeeError::Transport(
+                    format!(
+                        "no mock response for {} {}",
+                        request.method.as_str(),
+                        request.url
+                    )
+                    .into(),
+                )
+            });
+        state.requests.push(request);
+        response
+    }
+}
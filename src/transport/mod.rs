@@ -0,0 +1,22 @@
+#[cfg(all(feature = "hyper", not(target_arch = "wasm32")))]
+mod hyper_transport;
+mod mock;
+#[cfg(feature = "reqwest")]
+mod reqwest_transport;
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+mod tower;
+mod types;
+#[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+mod ureq_transport;
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+pub use self::tower::*;
+#[cfg(all(feature = "hyper", not(target_arch = "wasm32")))]
+pub use hyper_transport::*;
+pub use mock::*;
+#[cfg(feature = "reqwest")]
+pub use reqwest_transport::*;
+pub use types::*;
+#[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+pub use ureq_transport::*;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,56 @@
+use crate::transport::{HttpMethod, HttpRequest, HttpResponse, HttpTransport};
+use async_trait::async_trait;
+
+/// [`HttpTransport`] over a `reqwest::Client`, the default
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+pub(crate) fn reqwest_request(
+    client: &reqwest::Client,
+    method: reqwest::Method,
+    url: &str,
+    headers: impl IntoIterator<Item = (String, String)>,
+    body: Vec<u8>,
+) -> reqwest::RequestBuilder {
+    let mut builder = client.request(method, url);
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    match body.is_empty() {
+        true => builder,
+        false => builder.body(body),
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> crate::Result<HttpResponse> {
+        let method = match request.method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Post => reqwest::Method::POST,
+        };
+        let resp = reqwest_request(
+            &self.client,
+            method,
+            &request.url,
+            request.headers,
+            request.body,
+        )
+        .send()
+        .await?;
+
+        Ok(HttpResponse {
+            status: resp.status().as_u16(),
+            body: resp.bytes().await?.to_vec(),
+        })
+    }
+}
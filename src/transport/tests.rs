@@ -0,0 +1,258 @@
+use super::*;
+use crate::{errors::ZebedeeError, ZebedeeClient};
+
+const WALLET: &str = r#"{"success":true,"data":{"unit":"msats","balance":"1500"}}"#;
+
+/// Serves one request on a local port with `status` and `body`, returns the base url
+/// and the raw request that was received
+#[cfg(all(
+    any(feature = "reqwest", feature = "ureq", feature = "hyper"),
+    not(target_arch = "wasm32")
+))]
+async fn serve_once(status: u16, body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut received = Vec::new();
+        let mut buf = [0; 4096];
+        // headers and the small bodies sent here arrive before the reply is needed
+        loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            received.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&received);
+            if let Some((head, rest)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(|v| v.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if rest.len() >= length {
+                    break;
+                }
+            }
+        }
+        let reply = format!(
+            "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        socket.write_all(reply.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&received).into_owned()
+    });
+    (url, server)
+}
+
+#[tokio::test]
+async fn test_client_sends_through_mock_transport() {
+    let transport = MockTransport::new();
+    transport.respond(200, WALLET);
+    let client = ZebedeeClient::new("key")
+        .try_domain("https://zbd.test")
+        .unwrap()
+        .transport(transport.clone());
+
+    let wallet = client.get_wallet_details().await.unwrap();
+    assert_eq!(wallet.data.unwrap().balance, "1500");
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].method, HttpMethod::Get);
+    assert_eq!(requests[0].url, "https://zbd.test/v0/wallet");
+    assert_eq!(requests[0].header_value("APIKEY"), Some("key"));
+
+    // nothing queued any more
+    let err = client.get_wallet_details().await.unwrap_err();
+    assert!(matches!(err, ZebedeeError::Transport(_)));
+}
+
+#[tokio::test]
+async fn test_client_error_status_from_transport() {
+    let transport = MockTransport::new();
+    transport.respond_always(400, r#"{"success":false,"message":"No Charge"}"#);
+    let client = ZebedeeClient::new("key").transport(transport.clone());
+
+    let charge = crate::charges::Charge::default();
+    let err = client.create_charge(&charge).await.unwrap_err();
+    assert!(matches!(err, ZebedeeError::Api(_)));
+    assert_eq!(err.to_string(), "No Charge");
+
+    let requests = transport.requests();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["expiresIn"], 300);
+    let content_types = requests[0]
+        .headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .count();
+    assert_eq!(content_types, 1);
+}
+
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+#[tokio::test]
+async fn test_reqwest_transport() {
+    let (url, server) = serve_once(200, WALLET).await;
+    let client = ZebedeeClient::new("key")
+        .try_domain(url)
+        .unwrap()
+        .transport(ReqwestTransport::default());
+
+    let wallet = client.get_wallet_details().await.unwrap();
+    assert_eq!(wallet.data.unwrap().balance, "1500");
+    let received = server.await.unwrap();
+    assert!(received.starts_with("GET /v0/wallet HTTP/1.1"));
+    assert!(received.contains("apikey: key"));
+}
+
+#[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+#[tokio::test]
+async fn test_ureq_transport() {
+    let (url, server) = serve_once(400, r#"{"success":false,"message":"Bad amount"}"#).await;
+    let client = ZebedeeClient::new("key")
+        .try_domain(url)
+        .unwrap()
+        .transport(UreqTransport::default());
+
+    let charge = crate::charges::Charge::default();
+    let err = client.create_charge(&charge).await.unwrap_err();
+    assert_eq!(err.to_string(), "Bad amount");
+    let received = server.await.unwrap();
+    assert!(received.starts_with("POST /v0/charges HTTP/1.1"));
+    assert!(received.contains(r#""expiresIn":300"#));
+}
+
+#[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+#[test]
+fn test_ureq_transport_without_tokio() {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        let mut received = [0; 1024];
+        let _ = socket.read(&mut received).unwrap();
+        let body = r#"{"success":true,"data":{"ips":[]}}"#;
+        write!(
+            socket,
+            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+    });
+
+    let client = ZebedeeClient::new("key")
+        .try_domain(url)
+        .unwrap()
+        .transport(UreqTransport::default());
+    let ips = futures::executor::block_on(client.get_prod_ips()).unwrap();
+    assert!(ips.success);
+    server.join().unwrap();
+}
+
+#[cfg(all(feature = "hyper", not(target_arch = "wasm32")))]
+#[tokio::test]
+async fn test_hyper_transport() {
+    use hyper_util::{
+        client::legacy::{connect::HttpConnector, Client},
+        rt::TokioExecutor,
+    };
+
+    let (url, server) = serve_once(200, WALLET).await;
+    let client = Client::builder(TokioExecutor::new()).build(HttpConnector::new());
+    let client = ZebedeeClient::new("key")
+        .try_domain(url)
+        .unwrap()
+        .transport(HyperTransport::new(client));
+
+    let wallet = client.get_wallet_details().await.unwrap();
+    assert_eq!(wallet.data.unwrap().balance, "1500");
+    assert!(server.await.unwrap().contains("apikey: key"));
+}
+
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+#[tokio::test]
+async fn test_client_over_tower_service() {
+    use futures::future::{ready, Ready};
+    use std::{
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+    };
+
+    #[derive(Clone, Default)]
+    struct Echo {
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl tower_service::Service<http::Request<Vec<u8>>> for Echo {
+        type Response = http::Response<Vec<u8>>;
+        type Error = BoxError;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<Vec<u8>>) -> Self::Future {
+            self.seen.lock().unwrap().push(request.uri().to_string());
+            let body = br#"{"success":true,"data":{"ips":["3.225.112.64"]}}"#.to_vec();
+            ready(Ok(http::Response::new(body)))
+        }
+    }
+
+    let service = Echo::default();
+    let client = ZebedeeClient::new("key")
+        .try_domain("https://zbd.test")
+        .unwrap()
+        .transport_service(service.clone());
+
+    let ips = client.get_prod_ips().await.unwrap();
+    assert_eq!(ips.data.unwrap().ips, vec!["3.225.112.64"]);
+    assert_eq!(
+        *service.seen.lock().unwrap(),
+        vec!["https://zbd.test/v0/prod-ips"]
+    );
+}
+
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+#[tokio::test]
+async fn test_client_over_service_that_is_not_sync() {
+    use futures::future::{ready, Ready};
+    use std::{
+        cell::Cell,
+        task::{Context, Poll},
+    };
+
+    // Cell is Send but not Sync, like the boxed service in BoxCloneService
+    #[derive(Clone, Default)]
+    struct Counter {
+        calls: Cell<usize>,
+    }
+
+    impl tower_service::Service<http::Request<Vec<u8>>> for Counter {
+        type Response = http::Response<Vec<u8>>;
+        type Error = BoxError;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: http::Request<Vec<u8>>) -> Self::Future {
+            self.calls.set(self.calls.get() + 1);
+            let body = br#"{"success":true,"data":{"ips":[]}}"#.to_vec();
+            ready(Ok(http::Response::new(body)))
+        }
+    }
+
+    let client = ZebedeeClient::new("key")
+        .try_domain("https://zbd.test")
+        .unwrap()
+        .transport_service(Counter::default());
+    for _ in 0..2 {
+        assert!(client.get_prod_ips().await.unwrap().success);
+    }
+}
@@ -0,0 +1,123 @@
+#[cfg(feature = "reqwest")]
+use crate::transport::reqwest_request;
+use crate::{
+    errors::ZebedeeError,
+    transport::{HttpRequest, HttpResponse, HttpTransport},
+};
+use async_trait::async_trait;
+use futures::future::poll_fn;
+#[cfg(feature = "reqwest")]
+use futures::future::BoxFuture;
+#[cfg(feature = "reqwest")]
+use std::task::{Context, Poll};
+use std::{fmt::Debug, sync::Mutex};
+use tower_service::Service;
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Sends the requests of a `ZebedeeClient` through a tower service, see
+/// `ZebedeeClient::transport_service`
+pub(crate) struct TowerTransport<S> {
+    /// Only locked to clone the service, so services such as `BoxCloneService` need not be `Sync`
+    service: Mutex<S>,
+}
+
+impl<S> TowerTransport<S> {
+    pub(crate) fn new(service: S) -> Self {
+        Self {
+            service: Mutex::new(service),
+        }
+    }
+}
+
+impl<S> Debug for TowerTransport<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TowerTransport")
+    }
+}
+
+#[async_trait]
+impl<S> HttpTransport for TowerTransport<S>
+where
+    S: Service<http::Request<Vec<u8>>, Response = http::Response<Vec<u8>>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    async fn send(&self, request: HttpRequest) -> crate::Result<HttpResponse> {
+        let request = request.into_http()?;
+
+        // the ready service must handle this request, every request gets its own clone
+        let mut service = self
+            .service
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .map_err(|e| ZebedeeError::Transport(e.into()))?;
+        let response = service
+            .call(request)
+            .await
+            .map_err(|e| ZebedeeError::Transport(e.into()))?;
+
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            body: response.into_body(),
+        })
+    }
+}
+
+/// reqwest as a tower service, the innermost service when layering middleware for a `ZebedeeClient`
+///
+/// ```ignore
+/// let service = ServiceBuilder::new()
+///     .timeout(Duration::from_secs(10))
+///     .concurrency_limit(16)
+///     .service(ReqwestService::default());
+/// let client = ZebedeeClient::new(apikey).transport_service(service);
+/// ```
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestService {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestService {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Service<http::Request<Vec<u8>>> for ReqwestService {
+    type Response = http::Response<Vec<u8>>;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Vec<u8>>) -> Self::Future {
+        let client = self.client.clone();
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let method = reqwest::Method::from_bytes(parts.method.as_str().as_bytes())?;
+            let headers = parts
+                .headers
+                .iter()
+                .map(|(name, value)| Ok((name.as_str().to_owned(), value.to_str()?.to_owned())))
+                .collect::<Result<Vec<_>, http::header::ToStrError>>()?;
+
+            let resp = reqwest_request(&client, method, &parts.uri.to_string(), headers, body)
+                .send()
+                .await?;
+            let status = resp.status().as_u16();
+            let body = resp.bytes().await?.to_vec();
+
+            Ok(http::Response::builder().status(status).body(body)?)
+        })
+    }
+}
@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use std::{fmt::Debug, sync::Arc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+        }
+    }
+}
+
+/// Request built by `ZebedeeClient`, handed to its [`HttpTransport`]
+#[derive(Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// Empty for requests without a body
+    pub body: Vec<u8>,
+}
+
+/// Headers whose values are never printed
+const SECRET_HEADERS: [&str; 3] = ["apikey", "authorization", "usertoken"];
+
+/// Redacts credentials and leaves out the body, which can carry tokens and verifiers
+impl Debug for HttpRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let headers: Vec<(&str, &str)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                match SECRET_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+                    true => (name.as_str(), "[REDACTED]"),
+                    false => (name.as_str(), value.as_str()),
+                }
+            })
+            .collect();
+        f.debug_struct("HttpRequest")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &headers)
+            .field("body_len", &self.body.len())
+            .finish()
+    }
+}
+
+impl HttpRequest {
+    pub fn new(method: HttpMethod, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn get(url: impl Into<String>) -> Self {
+        Self::new(HttpMethod::Get, url)
+    }
+
+    pub fn post(url: impl Into<String>) -> Self {
+        Self::new(HttpMethod::Post, url)
+    }
+
+    /// Sets a header, replacing an earlier value with the same name
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_owned(), value.into()));
+        self
+    }
+
+    /// Value of the header `name`, compared case-insensitively
+    pub fn header_value(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, body: &T) -> crate::Result<Self> {
+        Ok(Self {
+            body: serde_json::to_vec(body)?,
+            ..self.header("Content-Type", "application/json")
+        })
+    }
+
+    #[cfg(all(any(feature = "tower", feature = "hyper"), not(target_arch = "wasm32")))]
+    pub(crate) fn into_http(self) -> crate::Result<http::Request<Vec<u8>>> {
+        let mut builder = http::Request::builder()
+            .method(self.method.as_str())
+            .uri(&self.url);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        builder
+            .body(self.body)
+            .map_err(|e| crate::errors::ZebedeeError::Transport(e.into()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// HTTP stack used by `ZebedeeClient`, set with `ZebedeeClient::transport`.
+///
+/// Non-2xx answers are returned as an [`HttpResponse`], errors are for requests
+/// that got no answer at all.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait HttpTransport: Send + Sync + Debug {
+    async fn send(&self, request: HttpRequest) -> crate::Result<HttpResponse>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T: HttpTransport + ?Sized> HttpTransport for Arc<T> {
+    async fn send(&self, request: HttpRequest) -> crate::Result<HttpResponse> {
+        (**self).send(request).await
+    }
+}
+
+/// Used when no HTTP backend feature is enabled
+#[cfg(not(any(
+    feature = "reqwest",
+    all(feature = "ureq", not(target_arch = "wasm32"))
+)))]
+#[derive(Debug)]
+struct NoTransport;
+
+#[cfg(not(any(
+    feature = "reqwest",
+    all(feature = "ureq", not(target_arch = "wasm32"))
+)))]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpTransport for NoTransport {
+    async fn send(&self, _request: HttpRequest) -> crate::Result<HttpResponse> {
+        Err(crate::errors::ErrorMsg::NoHttpTransport.into())
+    }
+}
+
+/// reqwest when enabled, otherwise ureq
+pub(crate) fn default_transport() -> Arc<dyn HttpTransport> {
+    #[cfg(feature = "reqwest")]
+    {
+        Arc::new(crate::transport::ReqwestTransport::default())
+    }
+    #[cfg(all(
+        not(feature = "reqwest"),
+        feature = "ureq",
+        not(target_arch = "wasm32")
+    ))]
+    {
+        Arc::new(crate::transport::UreqTransport::default())
+    }
+    #[cfg(not(any(
+        feature = "reqwest",
+        all(feature = "ureq", not(target_arch = "wasm32"))
+    )))]
+    {
+        Arc::new(NoTransport)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    errors::ZebedeeError,
+    transport::{HttpRequest, HttpResponse, HttpTransport},
+};
+use async_trait::async_trait;
+use futures::channel::oneshot;
+use std::{io::Read, time::Duration};
+
+/// [`HttpTransport`] over a blocking `ureq::Agent`.
+/// Requests run on the blocking thread pool of the current tokio runtime, or on a new
+/// thread when there is none.
+#[derive(Debug, Clone)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+/// Agent with a 10 second connect timeout and a 30 second read timeout
+impl Default for UreqTransport {
+    fn default() -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .timeout_read(Duration::from_secs(30))
+            .build();
+        Self::new(agent)
+    }
+}
+
+impl UreqTransport {
+    pub fn new(agent: ureq::Agent) -> Self {
+        Self { agent }
+    }
+
+    /// Sends `request` on the current thread
+    pub fn send_blocking(&self, request: HttpRequest) -> crate::Result<HttpResponse> {
+        let mut builder = self.agent.request(request.method.as_str(), &request.url);
+        for (name, value) in &request.headers {
+            builder = builder.set(name, value);
+        }
+        let result = match request.body.is_empty() {
+            true => builder.call(),
+            false => builder.send_bytes(&request.body),
+        };
+        let response = match result {
+            Ok(response) => response,
+            // ureq reports non-2xx answers as errors, they are regular responses here
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(ZebedeeError::Transport(e.into())),
+        };
+
+        let status = response.status();
+        let mut body = Vec::new();
+        response.into_reader().read_to_end(&mut body)?;
+        Ok(HttpResponse { status, body })
+    }
+}
+
+#[async_trait]
+impl HttpTransport for UreqTransport {
+    async fn send(&self, request: HttpRequest) -> crate::Result<HttpResponse> {
+        let transport = self.clone();
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            return handle
+                .spawn_blocking(move || transport.send_blocking(request))
+                .await
+                .map_err(|e| ZebedeeError::Transport(e.into()))?;
+        }
+
+        let (tx, rx) = oneshot::channel();
+        std::thread::Builder::new()
+            .name("zebedee-ureq".to_owned())
+            .spawn(move || {
+                let _ = tx.send(transport.send_blocking(request));
+            })?;
+        rx.await.map_err(|e| ZebedeeError::Transport(e.into()))?
+    }
+}
@@ -1,4 +1,12 @@
+mod prod_ips;
+mod region_gate;
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+mod region_layer;
 mod types;
+pub use prod_ips::*;
+pub use region_gate::*;
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+pub use region_layer::*;
 pub use types::*;
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,181 @@
+use crate::{api::ZebedeeApi, errors::ErrorMsg, ZebedeeClient};
+pub use ipnet::IpNet;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, RwLock},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+
+/// Shortest time between two background refreshes
+#[cfg(not(target_arch = "wasm32"))]
+pub const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// ZBD production ip addresses from `get_prod_ips`, used to verify that callbacks come from ZBD.
+///
+/// The list starts empty and is filled by [`ProdIpAllowlist::refresh`] or by the background
+/// task of [`ProdIpAllowlist::spawn`]. A failed refresh keeps the last good list.
+#[derive(Clone, Debug)]
+pub struct ProdIpAllowlist<A = ZebedeeClient> {
+    client: A,
+    #[cfg(not(target_arch = "wasm32"))]
+    interval: Duration,
+    trusted_proxies: Vec<IpNet>,
+    nets: Arc<RwLock<Arc<Vec<IpNet>>>>,
+}
+
+impl<A: ZebedeeApi> ProdIpAllowlist<A> {
+    pub fn new(client: A) -> Self {
+        Self {
+            client,
+            #[cfg(not(target_arch = "wasm32"))]
+            interval: Duration::from_secs(600),
+            trusted_proxies: Vec::new(),
+            nets: Arc::new(RwLock::new(Arc::new(Vec::new()))),
+        }
+    }
+
+    /// Time between two background refreshes, at least [`MIN_REFRESH_INTERVAL`],
+    /// defaults to 10 minutes
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn interval(self, interval: Duration) -> Self {
+        Self {
+            interval: interval.max(MIN_REFRESH_INTERVAL),
+            ..self
+        }
+    }
+
+    /// Proxy or load balancer allowed to set `X-Forwarded-For`, an ip or a CIDR range
+    pub fn trusted_proxy(mut self, proxy: impl Into<IpNet>) -> Self {
+        self.trusted_proxies.push(proxy.into());
+        self
+    }
+
+    /// Ip addresses and ranges currently allowed
+    pub fn nets(&self) -> Arc<Vec<IpNet>> {
+        self.nets.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub(crate) fn set(&self, nets: Vec<IpNet>) {
+        *self.nets.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(nets);
+    }
+
+    /// Fetches the list again and returns the number of entries.
+    /// On failure the previous list stays in use.
+    pub async fn refresh(&self) -> crate::Result<usize> {
+        let ips = self
+            .client
+            .get_prod_ips()
+            .await?
+            .data
+            .ok_or_else(|| ErrorMsg::BadPayloadData("no prod ips".to_owned()))?
+            .ips;
+        let nets = parse_ip_entries(&ips)?;
+        let count = nets.len();
+        self.set(nets);
+        Ok(count)
+    }
+
+    /// Refreshes the list right away and then at every interval on the current tokio runtime
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(&self) -> ProdIpAllowlistHandle
+    where
+        A: Clone + 'static,
+    {
+        let allowlist = self.clone();
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(allowlist.interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // errors keep the last good list until the next attempt
+                let _ = allowlist.refresh().await;
+            }
+        });
+        ProdIpAllowlistHandle { task }
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.nets().iter().any(|net| net.contains(&ip))
+    }
+
+    fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Address of the original client. `X-Forwarded-For` is only read when the `peer`
+    /// address is a trusted proxy, and is walked from the right until an untrusted hop.
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        let mut client = peer.to_canonical();
+        if !self.is_trusted_proxy(client) {
+            return client;
+        }
+        for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+            match parse_hop(hop) {
+                Some(ip) => {
+                    client = ip;
+                    if !self.is_trusted_proxy(ip) {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+        client
+    }
+
+    /// Whether a callback received from `peer` with this `X-Forwarded-For` header comes from ZBD
+    pub fn verify(&self, peer: IpAddr, forwarded_for: Option<&str>) -> bool {
+        self.contains(self.client_ip(peer, forwarded_for))
+    }
+}
+
+/// Stops the background refresh when dropped
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ProdIpAllowlistHandle {
+    task: JoinHandle<()>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ProdIpAllowlistHandle {
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for ProdIpAllowlistHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Parses `get_prod_ips` entries, single addresses or CIDR ranges
+pub fn parse_ip_entries<T: AsRef<str>>(entries: &[T]) -> crate::Result<Vec<IpNet>> {
+    entries
+        .iter()
+        .map(|entry| {
+            let entry = entry.as_ref().trim();
+            entry
+                .parse::<IpNet>()
+                .or_else(|_| {
+                    entry
+                        .parse::<IpAddr>()
+                        .map(|ip| IpNet::from(ip.to_canonical()))
+                })
+                .map(|net| net.trunc())
+                .map_err(|_| ErrorMsg::BadPayloadData(format!("bad prod ip {entry}")).into())
+        })
+        .collect()
+}
+
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    let hop = hop.trim();
+    hop.parse::<IpAddr>()
+        .or_else(|_| hop.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .ok()
+        .map(|ip| ip.to_canonical())
+}
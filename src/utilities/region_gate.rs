@@ -0,0 +1,228 @@
+use crate::{api::ZebedeeApi, errors::ErrorMsg, utilities::RegionIpData, Instant, ZebedeeClient};
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// What a [`RegionGate`] answers when the region of an ip cannot be looked up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RegionFailurePolicy {
+    /// Let the player through
+    Open,
+    /// Refuse the player
+    #[default]
+    Closed,
+}
+
+#[derive(Debug, Default)]
+struct RegionCache {
+    entries: HashMap<IpAddr, (Instant, Arc<RegionIpData>)>,
+    /// Held by the lookup of an ip in flight, later lookups of that ip wait for its answer
+    in_flight: HashMap<IpAddr, Arc<tokio::sync::Mutex<()>>>,
+}
+
+impl RegionCache {
+    fn get(&self, ip: &IpAddr, ttl: Duration) -> Option<Arc<RegionIpData>> {
+        self.entries
+            .get(ip)
+            .filter(|(at, _)| at.elapsed() < ttl)
+            .map(|(_, data)| data.clone())
+    }
+
+    /// Called before adding an entry. Once `max_entries` is reached the expired entries are
+    /// dropped, then the oldest ones until 90% of the cap is left, so the full scan only runs
+    /// every tenth of the cap.
+    fn make_room(&mut self, ttl: Duration, max_entries: usize) {
+        if self.entries.len() < max_entries {
+            return;
+        }
+        self.entries.retain(|_, (at, _)| at.elapsed() < ttl);
+
+        let target = max_entries - max_entries.div_ceil(10);
+        if self.entries.len() <= target {
+            return;
+        }
+        let mut by_age: Vec<(Instant, IpAddr)> = self
+            .entries
+            .iter()
+            .map(|(ip, (at, _))| (*at, *ip))
+            .collect();
+        by_age.sort_unstable();
+        let excess = by_age.len() - target;
+        for (_, ip) in by_age.into_iter().take(excess) {
+            self.entries.remove(&ip);
+        }
+    }
+}
+
+/// Refuses players outside the regions supported by ZBD, based on
+/// `get_is_supported_region_by_ip`. Lookups are cached per ip for `ttl`, concurrent lookups
+/// of the same ip share one request.
+///
+/// Deny lists win over allow lists, allow lists win over the API's `isSupported` answer.
+/// Countries and regions are compared case-insensitively with `ipCountry` and `ipRegion`,
+/// regions together with their country since region codes repeat across countries.
+pub struct RegionGate<A = ZebedeeClient> {
+    client: A,
+    ttl: Duration,
+    max_entries: usize,
+    policy: RegionFailurePolicy,
+    allow_countries: HashSet<String>,
+    deny_countries: HashSet<String>,
+    allow_regions: HashSet<(String, String)>,
+    deny_regions: HashSet<(String, String)>,
+    cache: Mutex<RegionCache>,
+}
+
+impl<A: ZebedeeApi> RegionGate<A> {
+    pub fn new(client: A) -> Self {
+        Self {
+            client,
+            ttl: Duration::from_secs(3600),
+            max_entries: 100_000,
+            policy: RegionFailurePolicy::default(),
+            allow_countries: HashSet::new(),
+            deny_countries: HashSet::new(),
+            allow_regions: HashSet::new(),
+            deny_regions: HashSet::new(),
+            cache: Mutex::new(RegionCache::default()),
+        }
+    }
+
+    /// How long the region of an ip is cached, defaults to 1 hour
+    pub fn ttl(self, ttl: Duration) -> Self {
+        Self { ttl, ..self }
+    }
+
+    /// Maximum number of cached ips, defaults to 100 000. Expired entries are dropped first
+    /// when it is reached, then the oldest ones.
+    pub fn max_entries(self, max_entries: usize) -> Self {
+        Self {
+            max_entries: max_entries.max(1),
+            ..self
+        }
+    }
+
+    /// Defaults to [`RegionFailurePolicy::Closed`]
+    pub fn failure_policy(self, policy: RegionFailurePolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    pub fn allow_country(mut self, country: &str) -> Self {
+        self.allow_countries.insert(country.to_uppercase());
+        self
+    }
+
+    pub fn deny_country(mut self, country: &str) -> Self {
+        self.deny_countries.insert(country.to_uppercase());
+        self
+    }
+
+    /// Allows `region` of `country`, e.g. `allow_region("US", "CA")`
+    pub fn allow_region(mut self, country: &str, region: &str) -> Self {
+        self.allow_regions
+            .insert((country.to_uppercase(), region.to_uppercase()));
+        self
+    }
+
+    /// Denies `region` of `country`, e.g. `deny_region("US", "NY")`
+    pub fn deny_region(mut self, country: &str, region: &str) -> Self {
+        self.deny_regions
+            .insert((country.to_uppercase(), region.to_uppercase()));
+        self
+    }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, RegionCache> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Region data of `ip`, from the cache when fresh
+    pub async fn lookup(&self, ip: IpAddr) -> crate::Result<Arc<RegionIpData>> {
+        let ip = normalize_ip(ip);
+        if let Some(data) = self.cache().get(&ip, self.ttl) {
+            return Ok(data);
+        }
+
+        let flight = self.cache().in_flight.entry(ip).or_default().clone();
+        let _turn = flight.lock().await;
+        // answered by the lookup we waited for
+        if let Some(data) = self.cache().get(&ip, self.ttl) {
+            return Ok(data);
+        }
+
+        let data = self
+            .client
+            .get_is_supported_region_by_ip(&ip.to_string())
+            .await
+            .and_then(|resp| {
+                resp.data.ok_or_else(|| {
+                    ErrorMsg::BadPayloadData(format!("no region data for {ip}")).into()
+                })
+            });
+        let mut cache = self.cache();
+        if cache
+            .in_flight
+            .get(&ip)
+            .is_some_and(|current| Arc::ptr_eq(current, &flight))
+        {
+            cache.in_flight.remove(&ip);
+        }
+        drop(cache);
+        Ok(self.insert(ip, data?))
+    }
+
+    pub(crate) fn insert(&self, ip: IpAddr, data: RegionIpData) -> Arc<RegionIpData> {
+        let data = Arc::new(data);
+        let mut cache = self.cache();
+        cache.make_room(self.ttl, self.max_entries);
+        cache
+            .entries
+            .insert(normalize_ip(ip), (Instant::now(), data.clone()));
+        data
+    }
+
+    /// Number of cached ips, expired ones included until they are dropped
+    pub fn len(&self) -> usize {
+        self.cache().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `ip` may use Bitcoin features, lookup failures follow the failure policy
+    pub async fn is_allowed(&self, ip: IpAddr) -> bool {
+        match self.lookup(ip).await {
+            Ok(data) => self.decide(&data),
+            Err(_) => self.policy == RegionFailurePolicy::Open,
+        }
+    }
+
+    /// Answer for requests whose ip is unknown
+    pub fn allows_unknown(&self) -> bool {
+        self.policy == RegionFailurePolicy::Open
+    }
+
+    fn decide(&self, data: &RegionIpData) -> bool {
+        let country = data.ip_country.to_uppercase();
+        let region = (country.clone(), data.ip_region.to_uppercase());
+        if self.deny_countries.contains(&country) || self.deny_regions.contains(&region) {
+            return false;
+        }
+        if self.allow_countries.contains(&country) || self.allow_regions.contains(&region) {
+            return true;
+        }
+        data.is_supported
+    }
+
+    pub fn clear(&self) {
+        self.cache().entries.clear();
+    }
+}
+
+/// IPv4-mapped IPv6 addresses such as `::ffff:1.2.3.4` are treated as the IPv4 address
+pub(crate) fn normalize_ip(ip: IpAddr) -> IpAddr {
+    ip.to_canonical()
+}
@@ -0,0 +1,117 @@
+use crate::{api::ZebedeeApi, utilities::RegionGate, ZebedeeClient};
+use http::{Extensions, HeaderMap, Request, Response, StatusCode};
+use std::{
+    future::Future,
+    net::IpAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+type IpExtractor = Arc<dyn Fn(&HeaderMap, &Extensions) -> Option<IpAddr> + Send + Sync>;
+
+/// Tower layer rejecting requests from unsupported regions with `403 Forbidden`
+/// before they reach the inner service.
+///
+/// With axum, read the peer address from `ConnectInfo`:
+///
+/// ```ignore
+/// RegionGateLayer::new(gate, |_, extensions| {
+///     extensions.get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip())
+/// })
+/// ```
+pub struct RegionGateLayer<A = ZebedeeClient> {
+    gate: Arc<RegionGate<A>>,
+    client_ip: IpExtractor,
+}
+
+// derived Clone would require `A: Clone`
+impl<A> Clone for RegionGateLayer<A> {
+    fn clone(&self) -> Self {
+        Self {
+            gate: self.gate.clone(),
+            client_ip: self.client_ip.clone(),
+        }
+    }
+}
+
+impl<A: ZebedeeApi> RegionGateLayer<A> {
+    /// `client_ip` finds the player's ip in the request headers or extensions
+    pub fn new<F>(gate: impl Into<Arc<RegionGate<A>>>, client_ip: F) -> Self
+    where
+        F: Fn(&HeaderMap, &Extensions) -> Option<IpAddr> + Send + Sync + 'static,
+    {
+        Self {
+            gate: gate.into(),
+            client_ip: Arc::new(client_ip),
+        }
+    }
+}
+
+impl<S, A> Layer<S> for RegionGateLayer<A> {
+    type Service = RegionGateService<S, A>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RegionGateService {
+            inner,
+            gate: self.gate.clone(),
+            client_ip: self.client_ip.clone(),
+        }
+    }
+}
+
+pub struct RegionGateService<S, A = ZebedeeClient> {
+    inner: S,
+    gate: Arc<RegionGate<A>>,
+    client_ip: IpExtractor,
+}
+
+impl<S: Clone, A> Clone for RegionGateService<S, A> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            gate: self.gate.clone(),
+            client_ip: self.client_ip.clone(),
+        }
+    }
+}
+
+impl<S, A, ReqBody, ResBody> Service<Request<ReqBody>> for RegionGateService<S, A>
+where
+    A: ZebedeeApi + 'static,
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    ReqBody: Send + 'static,
+    ResBody: Default,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        // the ready service must handle this request, leave a fresh clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let gate = self.gate.clone();
+        let ip = (self.client_ip)(request.headers(), request.extensions());
+
+        Box::pin(async move {
+            let allowed = match ip {
+                Some(ip) => gate.is_allowed(ip).await,
+                None => gate.allows_unknown(),
+            };
+            if !allowed {
+                let mut response = Response::new(ResBody::default());
+                *response.status_mut() = StatusCode::FORBIDDEN;
+                return Ok(response);
+            }
+            inner.call(request).await
+        })
+    }
+}
@@ -1,12 +1,15 @@
-use crate::ZebedeeClient;
-use std::env;
+use super::*;
+use crate::{
+    api::{Endpoint, FakeZebedeeApi, ZebedeeApi},
+    transport::{HttpRequest, HttpResponse, HttpTransport, MockTransport},
+    ZebedeeClient,
+};
+use serde_json::json;
+use std::sync::Arc;
 
 #[tokio::test]
 async fn test_get_is_supported_region_by_ip() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let ip = "3.225.112.64";
 
@@ -20,10 +23,7 @@ async fn test_get_is_supported_region_by_ip() {
 
 #[tokio::test]
 async fn test_get_prod_ips() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
 
     let r = zebedee_client.get_prod_ips().await.unwrap().success;
     assert!(r);
@@ -31,10 +31,219 @@ async fn test_get_prod_ips() {
 
 #[tokio::test]
 async fn test_get_btc_usd() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
     let r = zebedee_client.get_btc_usd().await.unwrap().success;
     assert!(r);
 }
+
+fn region(country: &str, region: &str, is_supported: bool) -> RegionIpData {
+    RegionIpData {
+        ip_address: String::new(),
+        is_supported,
+        ip_country: country.to_owned(),
+        ip_region: region.to_owned(),
+    }
+}
+
+fn offline_gate() -> RegionGate {
+    // no mock responses, lookups only succeed for cached ips
+    RegionGate::new(ZebedeeClient::new("apikey").transport(MockTransport::new()))
+}
+
+#[tokio::test]
+async fn test_region_gate() {
+    let gate = offline_gate()
+        .deny_region("us", "ny")
+        .allow_region("ca", "qc")
+        .allow_country("xx");
+    gate.insert("1.1.1.1".parse().unwrap(), region("US", "CA", true));
+    gate.insert("2.2.2.2".parse().unwrap(), region("US", "NY", true));
+    gate.insert("3.3.3.3".parse().unwrap(), region("XX", "", false));
+    gate.insert("4.4.4.4".parse().unwrap(), region("YY", "", false));
+    gate.insert("6.6.6.6".parse().unwrap(), region("GB", "NY", true));
+    gate.insert("7.7.7.7".parse().unwrap(), region("CA", "QC", false));
+    gate.insert("8.8.8.8".parse().unwrap(), region("FR", "QC", false));
+
+    assert!(gate.is_allowed("1.1.1.1".parse().unwrap()).await);
+    // IPv4-mapped addresses share the cache entry of the IPv4 address
+    assert!(gate.is_allowed("::ffff:1.1.1.1".parse().unwrap()).await);
+    assert!(!gate.is_allowed("2.2.2.2".parse().unwrap()).await);
+    assert!(gate.is_allowed("3.3.3.3".parse().unwrap()).await);
+    assert!(!gate.is_allowed("4.4.4.4".parse().unwrap()).await);
+    // regions only match within their own country
+    assert!(gate.is_allowed("6.6.6.6".parse().unwrap()).await);
+    assert!(gate.is_allowed("7.7.7.7".parse().unwrap()).await);
+    assert!(!gate.is_allowed("8.8.8.8".parse().unwrap()).await);
+
+    // lookup failures follow the failure policy
+    assert!(!gate.is_allowed("5.5.5.5".parse().unwrap()).await);
+    let gate = offline_gate().failure_policy(RegionFailurePolicy::Open);
+    assert!(gate.is_allowed("5.5.5.5".parse().unwrap()).await);
+}
+
+#[tokio::test]
+async fn test_region_gate_is_bounded() {
+    let gate = offline_gate().max_entries(10);
+    for i in 0..100u8 {
+        gate.insert([10, 0, 0, i].into(), region("US", "CA", true));
+        assert!(gate.len() <= 10);
+    }
+    assert!(gate.is_allowed([10, 0, 0, 99].into()).await);
+    assert!(!gate.is_allowed([10, 0, 0, 0].into()).await);
+}
+
+/// Yields once before answering, so concurrent lookups overlap
+#[derive(Debug)]
+struct YieldingTransport(MockTransport);
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl HttpTransport for YieldingTransport {
+    async fn send(&self, request: HttpRequest) -> crate::Result<HttpResponse> {
+        tokio::task::yield_now().await;
+        self.0.send(request).await
+    }
+}
+
+#[tokio::test]
+async fn test_region_gate_looks_up_an_ip_once() {
+    let transport = MockTransport::new();
+    // a single answer, a second request would fail
+    transport.respond(
+        200,
+        r#"{"success":true,"data":{"ipAddress":"5.5.5.5","isSupported":true,"ipCountry":"US","ipRegion":"CA"}}"#,
+    );
+    let gate = RegionGate::new(
+        ZebedeeClient::new("apikey").transport(YieldingTransport(transport.clone())),
+    );
+
+    let ip: std::net::IpAddr = "5.5.5.5".parse().unwrap();
+    let (a, b, c) = futures::join!(gate.is_allowed(ip), gate.is_allowed(ip), gate.lookup(ip));
+    assert!(a && b);
+    assert!(c.unwrap().is_supported);
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+#[tokio::test]
+async fn test_region_gate_layer() {
+    use http::{Request, Response, StatusCode};
+    use std::{
+        convert::Infallible,
+        future::{ready, Ready},
+        net::IpAddr,
+        task::{Context, Poll},
+    };
+    use tower_layer::Layer;
+    use tower_service::Service;
+
+    #[derive(Clone)]
+    struct Ok200;
+
+    impl Service<Request<()>> for Ok200 {
+        type Response = Response<String>;
+        type Error = Infallible;
+        type Future = Ready<Result<Response<String>, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Request<()>) -> Self::Future {
+            ready(Ok(Response::new(String::from("ok"))))
+        }
+    }
+
+    let gate = offline_gate();
+    gate.insert("1.1.1.1".parse().unwrap(), region("US", "CA", true));
+    gate.insert("2.2.2.2".parse().unwrap(), region("KP", "", false));
+    let mut service = RegionGateLayer::new(gate, |headers, _| {
+        headers
+            .get("x-player-ip")?
+            .to_str()
+            .ok()?
+            .parse::<IpAddr>()
+            .ok()
+    })
+    .layer(Ok200);
+
+    for (ip, status) in [
+        (Some("1.1.1.1"), StatusCode::OK),
+        (Some("2.2.2.2"), StatusCode::FORBIDDEN),
+        (None, StatusCode::FORBIDDEN),
+    ] {
+        let mut request = Request::builder();
+        if let Some(ip) = ip {
+            request = request.header("x-player-ip", ip);
+        }
+        let response = service.call(request.body(()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), status);
+    }
+}
+
+#[tokio::test]
+async fn test_prod_ip_allowlist() {
+    let nets = parse_ip_entries(&["3.225.112.64", "10.1.0.0/16", " 2600:1f18::/32 "]).unwrap();
+    assert!(parse_ip_entries(&["3.225.112"]).is_err());
+
+    let zebedee_client = ZebedeeClient::new("apikey").transport(MockTransport::new());
+    let allowlist = ProdIpAllowlist::new(zebedee_client)
+        .trusted_proxy("192.168.0.0/24".parse::<IpNet>().unwrap())
+        .trusted_proxy("127.0.0.1".parse::<std::net::IpAddr>().unwrap());
+    allowlist.set(nets);
+
+    assert!(allowlist.contains("3.225.112.64".parse().unwrap()));
+    assert!(allowlist.contains("::ffff:10.1.2.3".parse().unwrap()));
+    assert!(allowlist.contains("2600:1f18::1".parse().unwrap()));
+    assert!(!allowlist.contains("3.225.112.65".parse().unwrap()));
+
+    // a failed refresh keeps serving the last good list
+    assert!(allowlist.refresh().await.is_err());
+    assert_eq!(allowlist.nets().len(), 3);
+
+    let proxy = "192.168.0.7".parse().unwrap();
+    assert!(allowlist.verify(proxy, Some("6.6.6.6, 3.225.112.64, 127.0.0.1")));
+    assert!(!allowlist.verify(proxy, Some("3.225.112.64, 6.6.6.6")));
+    // headers from untrusted peers are ignored
+    let peer = "6.6.6.6".parse().unwrap();
+    assert!(!allowlist.verify(peer, Some("3.225.112.64")));
+    assert_eq!(
+        allowlist.client_ip(proxy, Some("[2600:1f18::1]:443")),
+        "2600:1f18::1".parse::<std::net::IpAddr>().unwrap()
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_prod_ip_allowlist_zero_interval() {
+    let transport = MockTransport::new();
+    transport.respond_always(200, r#"{"success":true,"data":{"ips":["3.225.112.64"]}}"#);
+    let zebedee_client = ZebedeeClient::new("apikey").transport(transport.clone());
+    let allowlist = ProdIpAllowlist::new(zebedee_client).interval(std::time::Duration::ZERO);
+
+    let _handle = allowlist.spawn();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    // refreshed once right away, the next refresh waits for the minimum interval
+    assert!(allowlist.contains("3.225.112.64".parse().unwrap()));
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_region_gate_over_shared_api() {
+    let fake = Arc::new(FakeZebedeeApi::new());
+    fake.respond(
+        Endpoint::GetIsSupportedRegionByIp,
+        &json!({
+            "success": true,
+            "data": { "ipAddress": "5.5.5.5", "isSupported": false, "ipCountry": "US", "ipRegion": "NY" }
+        }),
+    )
+    .unwrap();
+    let api: Arc<dyn ZebedeeApi> = fake.clone();
+    let gate = RegionGate::new(api);
+
+    assert!(!gate.is_allowed("5.5.5.5".parse().unwrap()).await);
+    assert!(!gate.is_allowed("::ffff:5.5.5.5".parse().unwrap()).await);
+    assert_eq!(fake.calls()[0].args, json!("5.5.5.5"));
+    assert_eq!(fake.call_count(Endpoint::GetIsSupportedRegionByIp), 1);
+}
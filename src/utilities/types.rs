@@ -18,7 +18,7 @@ pub struct IpData {
     pub ips: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionIpData {
     #[serde(rename = "ipAddress")]
     pub ip_address: String,
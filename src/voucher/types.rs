@@ -1,5 +1,6 @@
 use crate::{
     custom_deserializer::{deserialize_from_m_string, deserialize_from_string},
+    ids::{TransactionId, VoucherId, WalletId},
     models::UnitType,
 };
 use chrono::{DateTime, Utc};
@@ -13,12 +14,12 @@ pub struct VoucherData {
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(rename = "createTransactionId")]
-    pub create_transaction_id: String,
+    pub create_transaction_id: TransactionId,
     pub description: String,
     #[serde(deserialize_with = "deserialize_from_m_string")]
     pub fee: Option<u64>,
-    pub id: String,
+    pub id: VoucherId,
     pub unit: UnitType,
     #[serde(rename = "walletId")]
-    pub wallet_id: String,
+    pub wallet_id: WalletId,
 }
@@ -1,4 +1,6 @@
 mod types;
+mod watcher;
 pub use types::*;
+pub use watcher::*;
 #[cfg(test)]
 mod tests;
@@ -1,12 +1,9 @@
+use super::*;
 use crate::ZebedeeClient;
-use std::env;
 
 #[tokio::test]
 async fn test_wallet_details() {
-    let apikey: String = env::var("ZBD_API_KEY").unwrap();
-    let zbdenv: String =
-        env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
-    let zebedee_client = ZebedeeClient::new(apikey).domain(zbdenv);
+    let zebedee_client = ZebedeeClient::from_env().unwrap();
     let any_balance = 0..; //u64::MAX;
     let r = zebedee_client
         .get_wallet_details()
@@ -18,3 +15,153 @@ async fn test_wallet_details() {
     let r2: u64 = r.parse().unwrap();
     assert!(any_balance.contains(&r2));
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_balance_events() {
+    let thresholds = [10_000, 50_000];
+
+    let first = balance_events(None, 20_000, &thresholds);
+    assert_eq!(
+        first,
+        vec![
+            BalanceEvent::Changed {
+                previous: None,
+                current: 20_000
+            },
+            BalanceEvent::Below {
+                threshold: 50_000,
+                balance: 20_000
+            },
+        ]
+    );
+
+    assert!(balance_events(Some(20_000), 20_000, &thresholds).is_empty());
+
+    let dropped = balance_events(Some(20_000), 5_000, &thresholds);
+    assert_eq!(
+        dropped[1],
+        BalanceEvent::Below {
+            threshold: 10_000,
+            balance: 5_000
+        }
+    );
+    assert_eq!(dropped.len(), 2);
+
+    let recovered = balance_events(Some(5_000), 60_000, &thresholds);
+    assert_eq!(recovered.len(), 3);
+    assert!(recovered.contains(&BalanceEvent::Above {
+        threshold: 50_000,
+        balance: 60_000
+    }));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_top_up_waits_for_settlement_or_cooldown() {
+    use crate::Instant;
+    use std::time::Duration;
+
+    let top_up = TopUp {
+        from: ZebedeeClient::new("key"),
+        wallet_id: "b904ee02-ec0b-4fd4-b99f-1f2d3d0001a6".parse().unwrap(),
+        below: 10_000,
+        target: 50_000,
+    };
+    let cooldown = Duration::from_secs(600);
+    let start = Instant::now();
+
+    assert_eq!(top_up_amount(&top_up, 20_000, None, cooldown, start), None);
+    assert_eq!(
+        top_up_amount(&top_up, 5_000, None, cooldown, start),
+        Some(45_000)
+    );
+    // previous transfer not settled yet
+    let later = start + Duration::from_secs(60);
+    assert_eq!(
+        top_up_amount(&top_up, 5_000, Some(start), cooldown, later),
+        None
+    );
+    let after_cooldown = start + Duration::from_secs(601);
+    assert_eq!(
+        top_up_amount(&top_up, 5_000, Some(start), cooldown, after_cooldown),
+        Some(45_000)
+    );
+}
+
+#[tokio::test]
+async fn test_wallet_balance_msats() {
+    let msats = WalletData {
+        unit: String::from("msats"),
+        balance: String::from("1500"),
+    };
+    assert_eq!(msats.balance_msats().unwrap(), 1500);
+
+    let sats = WalletData {
+        unit: String::from("sats"),
+        balance: String::from("2"),
+    };
+    assert_eq!(sats.balance_msats().unwrap(), 2000);
+
+    let bad = WalletData {
+        unit: String::from("msats"),
+        balance: String::from("lots"),
+    };
+    assert!(bad.balance_msats().is_err());
+
+    for (unit, balance) in [("btc", "1"), ("sats", "18446744073709552")] {
+        let bad = WalletData {
+            unit: String::from(unit),
+            balance: String::from(balance),
+        };
+        assert!(bad.balance_msats().is_err(), "{balance} {unit}");
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_watcher_tops_up_with_a_full_channel_and_stops_without_receiver() {
+    use crate::api::{Endpoint, FakeZebedeeApi};
+    use serde_json::json;
+    use std::{sync::Arc, time::Duration};
+
+    let fake = Arc::new(FakeZebedeeApi::new());
+    fake.respond(
+        Endpoint::GetWalletDetails,
+        &json!({ "success": true, "data": { "unit": "msats", "balance": "5000" } }),
+    )
+    .unwrap();
+    fake.fail(Endpoint::InternalTransfer, "Insufficient balance");
+
+    let top_up = TopUp {
+        from: fake.clone(),
+        wallet_id: "0a872b22-d3e2-46c8-84af-139cce32a4c5".parse().unwrap(),
+        below: 10_000,
+        target: 50_000,
+    };
+    let (handle, events) = BalanceWatcher::new(fake.clone())
+        .interval(Duration::ZERO)
+        .threshold(10_000)
+        .capacity(1)
+        .top_up(top_up)
+        .spawn();
+
+    // the first event fills the channel, the top-up still goes out
+    for _ in 0..100 {
+        if fake.call_count(Endpoint::InternalTransfer) == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(fake.call_count(Endpoint::InternalTransfer), 1);
+    assert_eq!(events.len(), 1);
+
+    drop(events);
+    for _ in 0..300 {
+        if handle.is_finished() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(handle.is_finished());
+}
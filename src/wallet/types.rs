@@ -1,10 +1,21 @@
+use crate::{errors::ErrorMsg, models::UnitType, StdResp};
 use serde::{Deserialize, Serialize};
-use crate::StdResp;
 
 pub type WalletInfoResponse = StdResp<Option<WalletData>>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletData {
     pub unit: String,
     pub balance: String,
-}
\ No newline at end of file
+}
+
+impl WalletData {
+    /// Wallet balance converted to millisatoshis
+    pub fn balance_msats(&self) -> crate::Result<u64> {
+        let bad =
+            || ErrorMsg::BadPayloadData(format!("wallet balance {} {}", self.balance, self.unit));
+        let balance: u64 = self.balance.parse().map_err(|_| bad())?;
+        let unit: UnitType = self.unit.parse().map_err(|_| bad())?;
+        unit.checked_to_msats(balance).ok_or_else(|| bad().into())
+    }
+}
@@ -0,0 +1,275 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{api::ZebedeeApi, errors::ErrorMsg, internal_transfer::InternalTransfer, Instant};
+use crate::{ids::WalletId, ZebedeeClient};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{sync::Arc, time::Duration};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::{
+    sync::mpsc::{channel, Receiver, Sender},
+    task::JoinHandle,
+    time::MissedTickBehavior,
+};
+
+/// Shortest time between two polls
+#[cfg(not(target_arch = "wasm32"))]
+pub const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+#[cfg(not(target_arch = "wasm32"))]
+type ThresholdCallback = Arc<dyn Fn(&BalanceEvent) + Send + Sync>;
+
+/// Events emitted by a [`BalanceWatcher`]. All amounts are in millisatoshis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BalanceEvent {
+    /// Balance differs from the previous poll, `previous` is `None` on the first poll
+    Changed { previous: Option<u64>, current: u64 },
+    /// Balance dropped below a threshold
+    Below { threshold: u64, balance: u64 },
+    /// Balance went back up to or above a threshold
+    Above { threshold: u64, balance: u64 },
+    /// Watched wallet was topped up from the sibling project wallet
+    ToppedUp { amount: u64 },
+    /// Polling or topping up failed, the watcher keeps running
+    Error(String),
+}
+
+/// Refill the watched wallet from a sibling project wallet with an internal transfer
+#[derive(Clone, Debug)]
+pub struct TopUp<A = ZebedeeClient> {
+    /// Client of the project that sends the funds
+    pub from: A,
+    /// Wallet id of the watched project
+    pub wallet_id: WalletId,
+    /// Top up when the balance drops below this amount
+    pub below: u64,
+    /// Balance to restore the watched wallet to
+    pub target: u64,
+}
+
+/// Polls `get_wallet_details` at an interval and reports balance changes.
+/// Needs a tokio runtime, not available on wasm32.
+///
+/// Events go over a bounded channel and are dropped while it is full, so a slow receiver
+/// never holds up polling or top-ups. The watcher stops once the receiver is dropped.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct BalanceWatcher<A = ZebedeeClient> {
+    client: A,
+    interval: Duration,
+    thresholds: Vec<u64>,
+    callbacks: Vec<ThresholdCallback>,
+    top_up: Option<TopUp<A>>,
+    top_up_cooldown: Duration,
+    capacity: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<A: ZebedeeApi + 'static> BalanceWatcher<A> {
+    pub fn new(client: A) -> Self {
+        Self {
+            client,
+            interval: Duration::from_secs(60),
+            thresholds: Vec::new(),
+            callbacks: Vec::new(),
+            top_up: None,
+            top_up_cooldown: Duration::from_secs(600),
+            capacity: 64,
+        }
+    }
+
+    /// Time between two polls, at least [`MIN_INTERVAL`], defaults to 60 seconds
+    pub fn interval(self, interval: Duration) -> Self {
+        Self {
+            interval: interval.max(MIN_INTERVAL),
+            ..self
+        }
+    }
+
+    /// Adds a balance threshold in millisatoshis
+    pub fn threshold(mut self, threshold: u64) -> Self {
+        self.thresholds.push(threshold);
+        self
+    }
+
+    /// Callback fired with every [`BalanceEvent::Below`] and [`BalanceEvent::Above`] event
+    pub fn on_threshold<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&BalanceEvent) + Send + Sync + 'static,
+    {
+        self.callbacks.push(Arc::new(callback));
+        self
+    }
+
+    pub fn top_up(self, top_up: TopUp<A>) -> Self {
+        Self {
+            top_up: Some(top_up),
+            ..self
+        }
+    }
+
+    /// Time to wait after a top-up before sending another one while the balance is still
+    /// below `TopUp::below`, defaults to 10 minutes
+    pub fn top_up_cooldown(self, top_up_cooldown: Duration) -> Self {
+        Self {
+            top_up_cooldown,
+            ..self
+        }
+    }
+
+    /// Number of events buffered before new ones are dropped, defaults to 64
+    pub fn capacity(self, capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ..self
+        }
+    }
+
+    /// Starts polling on the current tokio runtime
+    pub fn spawn(self) -> (BalanceWatcherHandle, Receiver<BalanceEvent>) {
+        let (tx, rx) = channel(self.capacity);
+        let task = tokio::spawn(self.run(tx));
+        (BalanceWatcherHandle { task }, rx)
+    }
+
+    async fn run(self, tx: Sender<BalanceEvent>) {
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut previous = None;
+        let mut last_top_up = None;
+
+        loop {
+            ticker.tick().await;
+            if tx.is_closed() {
+                return;
+            }
+
+            let current = match self.poll().await {
+                Ok(current) => current,
+                Err(e) => {
+                    let _ = tx.try_send(BalanceEvent::Error(e.to_string()));
+                    continue;
+                }
+            };
+
+            for event in balance_events(previous, current, &self.thresholds) {
+                if matches!(
+                    event,
+                    BalanceEvent::Below { .. } | BalanceEvent::Above { .. }
+                ) {
+                    self.callbacks.iter().for_each(|callback| callback(&event));
+                }
+                let _ = tx.try_send(event);
+            }
+            previous = Some(current);
+
+            if let Some(top_up) = &self.top_up {
+                let now = Instant::now();
+                let due = top_up_amount(top_up, current, last_top_up, self.top_up_cooldown, now);
+                last_top_up = match due {
+                    Some(amount) => {
+                        // a failed transfer may still have gone through, it waits out the cooldown too
+                        let event = match Self::refill(top_up, amount).await {
+                            Ok(amount) => BalanceEvent::ToppedUp { amount },
+                            Err(e) => BalanceEvent::Error(e.to_string()),
+                        };
+                        let _ = tx.try_send(event);
+                        Some(now)
+                    }
+                    None if current >= top_up.below => None,
+                    None => last_top_up,
+                };
+            }
+        }
+    }
+
+    async fn poll(&self) -> crate::Result<u64> {
+        self.client
+            .get_wallet_details()
+            .await?
+            .data
+            .ok_or_else(|| ErrorMsg::BadPayloadData("no wallet data".to_owned()))?
+            .balance_msats()
+    }
+
+    async fn refill(top_up: &TopUp<A>, amount: u64) -> crate::Result<u64> {
+        let payload = InternalTransfer {
+            amount: amount.to_string(),
+            receiver_wallet_id: top_up.wallet_id.clone(),
+        };
+        top_up.from.internal_transfer(&payload).await?;
+        Ok(amount)
+    }
+}
+
+/// Stops the watcher when dropped
+#[cfg(not(target_arch = "wasm32"))]
+pub struct BalanceWatcherHandle {
+    task: JoinHandle<()>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl BalanceWatcherHandle {
+    pub fn stop(self) {
+        self.task.abort();
+    }
+
+    /// Whether the watcher stopped, which happens once its receiver is dropped
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for BalanceWatcherHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Amount to transfer when `current` is below `top_up.below`, unless a previous top-up at
+/// `last_top_up` has neither settled (balance back up) nor waited out `cooldown`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn top_up_amount<A>(
+    top_up: &TopUp<A>,
+    current: u64,
+    last_top_up: Option<Instant>,
+    cooldown: Duration,
+    now: Instant,
+) -> Option<u64> {
+    if current >= top_up.below || current >= top_up.target {
+        return None;
+    }
+    match last_top_up {
+        Some(last) if now.saturating_duration_since(last) < cooldown => None,
+        _ => Some(top_up.target - current),
+    }
+}
+
+/// Events produced by moving from the `previous` balance to the `current` one.
+/// On the first poll every threshold above the balance is reported as crossed.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn balance_events(
+    previous: Option<u64>,
+    current: u64,
+    thresholds: &[u64],
+) -> Vec<BalanceEvent> {
+    if previous == Some(current) {
+        return Vec::new();
+    }
+
+    let mut events = vec![BalanceEvent::Changed { previous, current }];
+    for &threshold in thresholds {
+        let was_below = previous.map(|p| p < threshold).unwrap_or(false);
+        let is_below = current < threshold;
+        match (was_below, is_below) {
+            (false, true) => events.push(BalanceEvent::Below {
+                threshold,
+                balance: current,
+            }),
+            (true, false) => events.push(BalanceEvent::Above {
+                threshold,
+                balance: current,
+            }),
+            _ => {}
+        }
+    }
+    events
+}
@@ -1,4 +1,4 @@
-use crate::StdResp;
+use crate::{ids::WithdrawalId, StdResp};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -18,7 +18,7 @@ pub struct WithdrawInvoiceData {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WithdrawalRequestsData {
-    pub id: String,
+    pub id: WithdrawalId,
     pub unit: String,
     pub amount: String,
     #[serde(rename = "createdAt")]
@@ -35,7 +35,7 @@ pub struct WithdrawalRequestsData {
 }
 
 /// Use this struct to create a well crafted json body for withdrawal requests
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WithdrawalReqest {
     #[serde(rename = "expiresIn")]
     pub expires_in: u32,